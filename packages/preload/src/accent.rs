@@ -0,0 +1,171 @@
+use anyhow::Result;
+use csv::{ReaderBuilder, Writer};
+use radix_trie::{Trie, TrieCommon};
+
+use crate::cmudict::encode_dict;
+
+type CmuTrie = Trie<String, String>;
+
+const VOWELS: &[&str] = &[
+    "AA", "AE", "AH", "AO", "AW", "AX", "AXR", "AY", "EH", "ER", "EY", "IH", "IX", "IY", "OH",
+    "OW", "OY", "UH", "UW", "UX",
+];
+
+// an arpabet phone split into its name and stress, such as ("AE", "1")
+fn split_stress(phone: &str) -> (&str, &str) {
+    let name = phone.trim_end_matches(|c: char| c.is_ascii_digit());
+    (name, &phone[name.len()..])
+}
+fn is_vowel(phone: &str) -> bool {
+    VOWELS.contains(&split_stress(phone).0)
+}
+
+// the vowel said in place of a vowel followed by an R that isn't said, and whether
+// a schwa is said after it, such as "near" N IH1 R -> N IH1 AX0
+fn non_rhotic_vowel(name: &str) -> (&str, bool) {
+    match name {
+        "IH" | "IY" => ("IH", true),
+        "EH" | "EY" => ("EH", true),
+        "UH" | "UW" => ("UH", true),
+        "AY" | "AW" => (name, true),
+        "OW" => ("AO", false),
+        _ => (name, false),
+    }
+}
+
+// the words said with the BATH vowel (ɑ) in Received Pronunciation but the TRAP
+// vowel (æ) in General American. others that look alike, such as "mass" and "gas",
+// keep the TRAP vowel
+const BATH_WORDS: &[&str] = &[
+    "advance", "after", "ask", "aunt", "basket", "bath", "blast", "branch", "brass", "cant",
+    "cast", "chance", "chant", "class", "craft", "dance", "disaster", "draft", "enhance", "fast",
+    "flask", "france", "glance", "glass", "graft", "grant", "grass", "half", "laugh", "last",
+    "mask", "master", "nasty", "pass", "past", "path", "plant", "plaster", "raft", "shaft",
+    "slant", "staff", "task", "vast",
+];
+
+// whether a word or one of its inflections (such as "passes" or "dancing") is a
+// BATH word
+fn is_bath_word(word: &str) -> bool {
+    let word: String = word
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    ["", "s", "es", "ed", "ing", "er", "ers"]
+        .iter()
+        .any(|suffix| {
+            word.strip_suffix(suffix).is_some_and(|stem| {
+                BATH_WORDS.contains(&stem) || BATH_WORDS.contains(&format!("{}e", stem).as_str())
+            })
+        })
+}
+
+// whether the AA of a word is spelled like the LOT vowel: with an o ("hot"), or an a
+// after w or qu ("want", "quality"). the ones spelled with a, such as "father" and
+// "calm", are the PALM vowel, which stays ɑ
+fn is_lot_spelling(word: &str) -> bool {
+    let word = word.to_ascii_lowercase();
+    word.contains('o') || word.contains("wa") || word.contains("qua")
+}
+
+// turns a General American pronunciation of a word, like the ones in cmudict, into
+// Received Pronunciation with a few rules
+pub fn to_rp(word: &str, pronounce: &str) -> String {
+    let bath = is_bath_word(word);
+    let lot = is_lot_spelling(word);
+    let phones: Vec<&str> = pronounce.split_whitespace().collect();
+    let mut out: Vec<String> = Vec::with_capacity(phones.len());
+    for (i, phone) in phones.iter().enumerate() {
+        let (name, stress) = split_stress(phone);
+        let next = phones.get(i + 1).copied();
+        let next_is_vowel = next.is_some_and(is_vowel);
+        match name {
+            // BATH: the stressed TRAP vowel of a BATH word, such as "dance"
+            "AE" if bath && stress == "1" => out.push("AA1".to_owned()),
+            // LOT: the short rounded vowel (OH, ɒ) rather than the one of THOUGHT,
+            // before any consonant but R
+            "AA" if lot && next.is_some_and(|next| !is_vowel(next) && next != "R") => {
+                out.push(format!("OH{}", stress));
+            }
+            // cmudict writes unstressed schwas like the STRUT vowel
+            "AH" if stress == "0" => out.push("AX0".to_owned()),
+            // lettER is a schwa, NURSE keeps its vowel. both are still said with
+            // an R before another vowel
+            "ER" => {
+                out.push(if stress == "0" {
+                    "AX0".to_owned()
+                } else {
+                    phone.to_string()
+                });
+                if next_is_vowel {
+                    out.push("R".to_owned());
+                }
+            }
+            // non-rhotic: an R is only said before a vowel
+            "R" if !next_is_vowel && out.last().is_some_and(|last| is_vowel(last)) => {
+                let last = out.pop().unwrap_or_default();
+                let (name, stress) = split_stress(&last);
+                let (vowel, schwa) = non_rhotic_vowel(name);
+                out.push(format!("{}{}", vowel, stress));
+                if schwa {
+                    out.push("AX0".to_owned());
+                }
+            }
+            "R" if !next_is_vowel => {}
+            _ => out.push(phone.to_string()),
+        }
+    }
+    out.join(" ")
+}
+
+// the words of cmudict whose pronunciation changes in Received Pronunciation, in the
+// same format as cmudict.bin. the runtime looks words up in it before cmudict
+pub fn get_rp_dict(trie: &CmuTrie) -> Result<Vec<u8>> {
+    let mut lines: Vec<(String, String)> = trie
+        .iter()
+        .filter_map(|(word, pronounce)| {
+            let rp = to_rp(word, pronounce);
+            (rp != *pronounce).then(|| (word.to_owned(), rp))
+        })
+        .collect();
+    lines.sort_by(|(word, _), (other_word, _)| word.cmp(other_word));
+    encode_dict(&lines)
+}
+
+// frameworks.csv with Received Pronunciation
+pub fn get_rp_frameworks(frameworks: &[u8]) -> Result<Vec<u8>> {
+    let mut wtr = Writer::from_writer(Vec::new());
+    for record in ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(frameworks)
+        .into_records()
+    {
+        let record = record?;
+        let mut fields: Vec<String> = record.iter().map(str::to_owned).collect();
+        if let [name, pronounce, ..] = fields.as_mut_slice() {
+            *pronounce = to_rp(name, pronounce);
+        }
+        wtr.write_record(&fields)?;
+    }
+    Ok(wtr.into_inner()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_rp;
+
+    #[test]
+    fn bath_and_lot_only_change_their_words() {
+        // BATH words, and ones that only look like them
+        assert_eq!(to_rp("PASS", "P AE1 S"), "P AA1 S");
+        assert_eq!(to_rp("DANCING", "D AE1 N S IH0 NG"), "D AA1 N S IH0 NG");
+        assert_eq!(to_rp("MASS", "M AE1 S"), "M AE1 S");
+        assert_eq!(to_rp("GAS", "G AE1 S"), "G AE1 S");
+        // LOT words, and PALM words said with the same vowel in General American
+        assert_eq!(to_rp("HOT", "HH AA1 T"), "HH OH1 T");
+        assert_eq!(to_rp("WANT", "W AA1 N T"), "W OH1 N T");
+        assert_eq!(to_rp("FATHER", "F AA1 DH ER0"), "F AA1 DH AX0");
+        assert_eq!(to_rp("CALM", "K AA1 M"), "K AA1 M");
+    }
+}
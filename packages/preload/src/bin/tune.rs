@@ -0,0 +1,193 @@
+// fits the `DistanceConfig` weights and model to labeled (word, framework, label) pairs
+// usage: cargo run --release --bin tune -- [pairs.csv] [out.json]
+use anyhow::{anyhow, Context, Result};
+use csv::ReaderBuilder;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use vite_wasm_functions::framework_data::read_frameworks;
+use vite_wasm_functions::pronounce::{DistanceConfig, DistanceModel, Pronounce, PronounceBuilder};
+
+const DEFAULT_PAIRS_PATH: &str = "./tables/similarity-pairs.csv";
+const DEFAULT_OUT_PATH: &str = "./output/distance.json";
+// how much a parameter is scaled by on each step of the search
+const STEPS: &[f32] = &[0.5, 0.8, 1.25, 2.0];
+const MAX_ROUNDS: usize = 20;
+
+struct Pair {
+    word: Pronounce,
+    framework: Pronounce,
+    // higher means the pair sounds more alike
+    score: f32,
+}
+
+fn parse_label(label: &str) -> Result<f32> {
+    match label.trim().to_ascii_lowercase().as_str() {
+        "good" => Ok(1.0),
+        "bad" => Ok(0.0),
+        other => other
+            .parse::<f32>()
+            .with_context(|| format!("invalid label: {}", label)),
+    }
+}
+
+fn read_pairs(path: &str, builder: &PronounceBuilder) -> Result<Vec<Pair>> {
+    let frameworks: HashMap<String, Pronounce> = read_frameworks()
+        .into_iter()
+        .filter_map(|(name, arpabet, _)| {
+            let pronounce = builder.pronounce_from_arpabet(&arpabet)?;
+            Some((name.to_ascii_lowercase(), pronounce))
+        })
+        .collect();
+
+    let mut pairs = Vec::new();
+    for record in ReaderBuilder::new()
+        .has_headers(false)
+        .comment(Some(b'#'))
+        .from_path(path)?
+        .into_records()
+    {
+        let record = record?;
+        let (word, framework, label) = match (record.get(0), record.get(1), record.get(2)) {
+            (Some(word), Some(framework), Some(label)) => (word.trim(), framework.trim(), label),
+            _ => {
+                return Err(anyhow!(
+                    "expected word,framework,label but got {:?}",
+                    record
+                ))
+            }
+        };
+        let framework_pronounce = frameworks
+            .get(&framework.to_ascii_lowercase())
+            .ok_or_else(|| anyhow!("unknown framework: {}", framework))?;
+        let word_pronounce = builder.pronounce(word);
+        if word_pronounce.syllable_count() == 0 {
+            eprintln!("skipping word not in dictionary: {}", word);
+            continue;
+        }
+        pairs.push(Pair {
+            word: word_pronounce,
+            framework: framework_pronounce.clone(),
+            score: parse_label(label)?,
+        });
+    }
+    Ok(pairs)
+}
+
+// fraction of pairs with different scores whose distances are ordered the same way
+fn ranking_accuracy(pairs: &[Pair], builder: &PronounceBuilder, config: &DistanceConfig) -> f32 {
+    let phone_distances = builder.distances(config);
+    let distances: Vec<f32> = pairs
+        .iter()
+        .map(|pair| pair.word.distance(&pair.framework, &phone_distances))
+        .collect();
+    let mut correct = 0.0;
+    let mut total = 0.0;
+    for i in 0..pairs.len() {
+        for j in 0..pairs.len() {
+            if pairs[i].score <= pairs[j].score {
+                continue;
+            }
+            total += 1.0;
+            // the better pair should be closer, ties count as half right
+            if distances[i] < distances[j] {
+                correct += 1.0;
+            } else if distances[i] == distances[j] {
+                correct += 0.5;
+            }
+        }
+    }
+    if total == 0.0 {
+        return 0.0;
+    }
+    correct / total
+}
+
+// the weights of both models, the ones the model in use ignores never help so they stay put
+const PARAM_COUNT: usize = 16;
+fn params(config: &mut DistanceConfig) -> [(&'static str, &mut f32); PARAM_COUNT] {
+    [
+        ("vowel_weight", &mut config.vowel_weight),
+        ("consonant_weight", &mut config.consonant_weight),
+        ("vowel_imperfect_punish", &mut config.vowel_imperfect_punish),
+        ("consonant_row_punish", &mut config.consonant_row_punish),
+        ("consonant_col_punish", &mut config.consonant_col_punish),
+        ("consonant_scale", &mut config.consonant_scale),
+        ("silent_punish", &mut config.silent_punish),
+        ("glide_weight", &mut config.glide_weight),
+        ("place_weight", &mut config.place_weight),
+        ("manner_weight", &mut config.manner_weight),
+        ("voicing_weight", &mut config.voicing_weight),
+        ("rounding_weight", &mut config.rounding_weight),
+        ("nasality_weight", &mut config.nasality_weight),
+        ("length_weight", &mut config.length_weight),
+        ("offglide_weight", &mut config.offglide_weight),
+        ("feature_scale", &mut config.feature_scale),
+    ]
+}
+
+// coordinate descent: scale one parameter at a time, keeping any change that helps
+fn search(
+    pairs: &[Pair],
+    builder: &PronounceBuilder,
+    start: DistanceConfig,
+) -> (DistanceConfig, f32) {
+    let mut best = start;
+    let mut best_accuracy = ranking_accuracy(pairs, builder, &best);
+    println!("starting accuracy: {:.4}", best_accuracy);
+    for round in 0..MAX_ROUNDS {
+        let mut improved = false;
+        for param in 0..PARAM_COUNT {
+            for step in STEPS {
+                let mut candidate = best.clone();
+                let (name, value) = &mut params(&mut candidate)[param];
+                **value *= step;
+                let accuracy = ranking_accuracy(pairs, builder, &candidate);
+                if accuracy > best_accuracy {
+                    println!(
+                        "round {}: {} *= {} -> accuracy {:.4}",
+                        round, name, step, accuracy
+                    );
+                    best = candidate;
+                    best_accuracy = accuracy;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    (best, best_accuracy)
+}
+
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let pairs_path = args.next().unwrap_or_else(|| DEFAULT_PAIRS_PATH.to_owned());
+    let out_path = args.next().unwrap_or_else(|| DEFAULT_OUT_PATH.to_owned());
+
+    let builder = PronounceBuilder::new();
+    let pairs = read_pairs(&pairs_path, &builder)?;
+    println!("read {} pairs from {}", pairs.len(), pairs_path);
+    // tunes each model from the defaults and keeps whichever ranks the pairs best
+    let (config, accuracy) = [DistanceModel::Coordinates, DistanceModel::Features]
+        .into_iter()
+        .map(|model| {
+            println!("tuning the {:?} model", model);
+            let start = DistanceConfig {
+                model,
+                ..DistanceConfig::default()
+            };
+            search(&pairs, &builder, start)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .ok_or_else(|| anyhow!("no models to tune"))?;
+    println!(
+        "final accuracy: {:.4} with the {:?} model",
+        accuracy, config.model
+    );
+
+    fs::write(&out_path, config.to_json())?;
+    println!("wrote config to {}", out_path);
+    Ok(())
+}
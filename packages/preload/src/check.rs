@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+use std::fs;
+
+// how many differing lines of each file are printed
+const MAX_SHOWN: usize = 10;
+
+fn print_lines(prefix: &str, lines: &[&str]) {
+    for line in lines.iter().take(MAX_SHOWN) {
+        println!("  {} {}", prefix, line);
+    }
+    if lines.len() > MAX_SHOWN {
+        println!("  {} ... and {} more", prefix, lines.len() - MAX_SHOWN);
+    }
+}
+
+// compares generated (path, contents) with the files on disk, printing the differences,
+// and returns how many files differ
+pub fn check_outputs(outputs: &[(String, Vec<u8>)]) -> usize {
+    let mut differing = 0;
+    for (path, contents) in outputs {
+        let committed = match fs::read(path) {
+            Ok(committed) => committed,
+            Err(err) => {
+                println!("{}: can't read ({})", path, err);
+                differing += 1;
+                continue;
+            }
+        };
+        if committed == *contents {
+            continue;
+        }
+        differing += 1;
+        match (
+            std::str::from_utf8(&committed),
+            std::str::from_utf8(contents),
+        ) {
+            (Ok(committed), Ok(generated)) => {
+                let committed_lines: HashSet<&str> = committed.lines().collect();
+                let generated_lines: HashSet<&str> = generated.lines().collect();
+                let removed: Vec<&str> = committed
+                    .lines()
+                    .filter(|line| !generated_lines.contains(line))
+                    .collect();
+                let added: Vec<&str> = generated
+                    .lines()
+                    .filter(|line| !committed_lines.contains(line))
+                    .collect();
+                println!(
+                    "{}: {} lines removed, {} lines added",
+                    path,
+                    removed.len(),
+                    added.len()
+                );
+                print_lines("-", &removed);
+                print_lines("+", &added);
+                if removed.is_empty() && added.is_empty() {
+                    println!("  the same lines in a different order");
+                }
+            }
+            // binary files like cmudict.bin
+            _ => println!(
+                "{}: differs ({} bytes committed, {} bytes generated)",
+                path,
+                committed.len(),
+                contents.len()
+            ),
+        }
+    }
+    differing
+}
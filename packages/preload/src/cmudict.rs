@@ -1,14 +1,13 @@
-use anyhow::Result;
-use flate2::write::GzEncoder;
-use flate2::Compression;
+use anyhow::{anyhow, Result};
+use fst::{Map, MapBuilder, Streamer};
+use itertools::Itertools;
 use radix_trie::Trie;
 use reqwest::blocking::get;
-use std::fs::File;
-use std::io::Write;
 
 type CmuTrie = Trie<String, String>;
 const URL: &str = "https://raw.githubusercontent.com/Alexir/CMUdict/master/cmudict-0.7b";
-pub fn get_cmudict(out_path: &str) -> Result<CmuTrie> {
+// the binary dictionary, and a Trie so we can get framework pronounciations
+pub fn get_cmudict() -> Result<(Vec<u8>, CmuTrie)> {
     let resp = get(URL)?;
     let mut lines: Vec<(String, String)> = resp
         .text()?
@@ -32,22 +31,142 @@ pub fn get_cmudict(out_path: &str) -> Result<CmuTrie> {
             )
         })
         .collect();
-    // remove multiple pronounciations of the same word
+    // remove multiple pronounciations of the same word, keeping the last one like a trie would,
+    // and sort because the binary format needs sorted words
+    lines.reverse();
+    lines.sort_by(|(word, _), (other_word, _)| word.cmp(other_word));
     lines.dedup_by(|(word, _), (other_word, _)| word == other_word);
 
-    let mut encoder = GzEncoder::new(File::create(out_path)?, Compression::default());
-    encoder.write_all(
-        lines
-            .iter()
-            .map(|(word, pronounce)| format!("{}  {}", word, pronounce))
-            .collect::<Vec<_>>()
-            .join("\n")
-            .as_bytes(),
-    )?;
-    encoder.finish()?;
-    // we return a Trie so we can get framework pronounciations
-    Ok(lines
+    let trie = lines
         .iter()
         .map(|(word, pronounce)| (word.to_owned(), pronounce.to_owned()))
-        .collect())
+        .collect();
+    Ok((encode_dict(&lines)?, trie))
+}
+
+const MAGIC: &[u8] = b"FSD1";
+
+// one byte per phone: the index of the phone in the phone table in the low 6 bits,
+// and the stress in the high 2 bits, 0 for none and 1 to 3 for stress 0 to 2
+fn encode_phone(phone: &str, phones: &[&str]) -> Result<u8> {
+    let name = phone.trim_end_matches(|c: char| c.is_ascii_digit());
+    let index = phones
+        .iter()
+        .position(|other| *other == name)
+        .ok_or_else(|| anyhow!("unknown phone {}", phone))?;
+    let stress = match &phone[name.len()..] {
+        "" => 0,
+        "0" => 1,
+        "1" => 2,
+        "2" => 3,
+        other => return Err(anyhow!("unknown stress {}", other)),
+    };
+    Ok(index as u8 | stress << 6)
+}
+
+// encodes sorted (word, pronounciation) pairs so the runtime can look words up
+// without parsing anything first:
+// - the magic bytes "FSD1"
+// - the phone table: a count, then each phone name prefixed with its length
+// - the length of the fst as a little endian u32, then the fst itself,
+//   which maps each word to the offset of its pronounciation
+// - the pronounciations: a length, then that many encoded phones
+// it isn't compressed, so it is about twice the size of the gzipped cmudict (1.7MB), but
+// gzipped for the network it is about the same (1MB), and words are looked up in place
+// instead of decompressing and parsing every word at startup
+pub fn encode_dict(lines: &[(String, String)]) -> Result<Vec<u8>> {
+    let phones: Vec<&str> = lines
+        .iter()
+        .flat_map(|(_, pronounce)| pronounce.split_whitespace())
+        .map(|phone| phone.trim_end_matches(|c: char| c.is_ascii_digit()))
+        .unique()
+        .sorted()
+        .collect();
+    if phones.len() > 64 {
+        return Err(anyhow!(
+            "too many phones to fit in 6 bits: {}",
+            phones.len()
+        ));
+    }
+
+    let mut map = MapBuilder::memory();
+    let mut pronounces = Vec::new();
+    for (word, pronounce) in lines {
+        map.insert(word, pronounces.len() as u64)?;
+        let encoded = pronounce
+            .split_whitespace()
+            .map(|phone| encode_phone(phone, &phones))
+            .collect::<Result<Vec<u8>>>()?;
+        pronounces.push(encoded.len() as u8);
+        pronounces.extend(encoded);
+    }
+    let map = map.into_inner()?;
+
+    let mut out = MAGIC.to_vec();
+    out.push(phones.len() as u8);
+    for phone in phones {
+        out.push(phone.len() as u8);
+        out.extend(phone.as_bytes());
+    }
+    out.extend((map.len() as u32).to_le_bytes());
+    out.extend(map);
+    out.extend(pronounces);
+    Ok(out)
+}
+
+// reads a dictionary written by `encode_dict` back into a Trie, so outputs made from
+// cmudict can be checked against the committed cmudict.bin without fetching it again
+pub fn decode_dict(data: &[u8]) -> Result<CmuTrie> {
+    let truncated = || anyhow!("dictionary is truncated");
+    if !data.starts_with(MAGIC) {
+        return Err(anyhow!("dictionary does not start with FSD1"));
+    }
+    let mut offset = MAGIC.len();
+    let phone_count = *data.get(offset).ok_or_else(truncated)?;
+    offset += 1;
+    let mut phones = Vec::with_capacity(phone_count as usize);
+    for _ in 0..phone_count {
+        let len = *data.get(offset).ok_or_else(truncated)? as usize;
+        let name = data
+            .get(offset + 1..offset + 1 + len)
+            .ok_or_else(truncated)?;
+        phones.push(std::str::from_utf8(name)?);
+        offset += 1 + len;
+    }
+    let map_len = u32::from_le_bytes(
+        data.get(offset..offset + 4)
+            .ok_or_else(truncated)?
+            .try_into()?,
+    ) as usize;
+    offset += 4;
+    let map = Map::new(data.get(offset..offset + map_len).ok_or_else(truncated)?)?;
+    let pronounces = &data[offset + map_len..];
+
+    let mut trie = CmuTrie::new();
+    let mut stream = map.stream();
+    while let Some((word, start)) = stream.next() {
+        let start = start as usize;
+        let len = *pronounces.get(start).ok_or_else(truncated)? as usize;
+        let encoded = pronounces
+            .get(start + 1..start + 1 + len)
+            .ok_or_else(truncated)?;
+        let pronounce = encoded
+            .iter()
+            .map(|byte| {
+                let name = phones
+                    .get((byte & 0x3f) as usize)
+                    .ok_or_else(|| anyhow!("unknown phone {}", byte & 0x3f))?;
+                let stress = match byte >> 6 {
+                    0 => "",
+                    1 => "0",
+                    2 => "1",
+                    _ => "2",
+                };
+                Ok(format!("{}{}", name, stress))
+            })
+            .collect::<Result<Vec<String>>>()?
+            .join(" ");
+        trie.insert(String::from_utf8(word.to_vec())?, pronounce);
+    }
+    Ok(trie)
 }
@@ -156,7 +156,7 @@ fn get_framework_pronounce(name: &str, trie: &CmuTrie) -> String {
         .join(" ")
 }
 
-pub fn get_frameworks(out_path: &str, trie: CmuTrie) -> Result<()> {
+pub fn get_frameworks(trie: &CmuTrie) -> Result<Vec<u8>> {
     let resp = get(URL)?;
     let frameworks: Value = serde_json::from_str(&resp.text()?)?;
     let mut sorted = frameworks["projects"]
@@ -222,12 +222,12 @@ pub fn get_frameworks(out_path: &str, trie: CmuTrie) -> Result<()> {
                     .join(" "),
             )
         })
-        .map(|(stars, name)| (stars, name.clone(), get_framework_pronounce(&name, &trie)))
+        .map(|(stars, name)| (stars, name.clone(), get_framework_pronounce(&name, trie)))
         .collect();
     // write to csv
-    let mut wtr = Writer::from_path(out_path)?;
+    let mut wtr = Writer::from_writer(Vec::new());
     for (stars, name, pronounce) in ret {
         wtr.write_record(&[name, pronounce, stars.to_string()])?;
     }
-    Ok(())
+    Ok(wtr.into_inner()?)
 }
@@ -1,16 +1,67 @@
+mod accent;
+mod check;
 mod cmudict;
 mod frameworks;
+mod phrases;
 mod tables;
 
-use cmudict::get_cmudict;
+use accent::{get_rp_dict, get_rp_frameworks};
+use check::check_outputs;
+use cmudict::{decode_dict, get_cmudict};
 use frameworks::get_frameworks;
+use phrases::get_phrases;
 use tables::get_arpabet_pronounce;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use radix_trie::Trie;
+use std::env;
+use std::fs;
 
+type CmuTrie = Trie<String, String>;
+
+// everything made from cmudict and frameworks.csv without fetching anything,
+// as (path, contents)
+fn derived_outputs(cmu_trie: &CmuTrie, frameworks: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut outputs = vec![
+        ("./output/cmudict-rp.bin".to_owned(), get_rp_dict(cmu_trie)?),
+        (
+            "./output/frameworks-rp.csv".to_owned(),
+            get_rp_frameworks(frameworks)?,
+        ),
+        ("./output/phrases.csv".to_owned(), get_phrases(cmu_trie)?),
+    ];
+    for (name, contents) in get_arpabet_pronounce()? {
+        outputs.push((format!("./output/pronounce/{}", name), contents));
+    }
+    Ok(outputs)
+}
+
+// usage: cargo run --release -- [--check]
+// --check regenerates the outputs that don't need the network in memory, from the
+// committed cmudict.bin and frameworks.csv, and reports how they differ from ./output.
+// cmudict.bin and frameworks.csv themselves aren't checked, because the cmudict
+// upstream and the star counts change over time
 fn main() -> Result<()> {
-    let cmu_trie = get_cmudict("./output/cmudict.gz")?;
-    get_frameworks("./output/frameworks.csv", cmu_trie)?;
-    get_arpabet_pronounce("./output/pronounce")?;
+    let check = env::args().skip(1).any(|arg| arg == "--check");
+
+    if check {
+        let cmu_trie = decode_dict(&fs::read("./output/cmudict.bin")?)?;
+        let frameworks = fs::read("./output/frameworks.csv")?;
+        let differing = check_outputs(&derived_outputs(&cmu_trie, &frameworks)?);
+        if differing > 0 {
+            return Err(anyhow!("{} output files are out of date", differing));
+        }
+        println!("all output files are up to date");
+        return Ok(());
+    }
+
+    let (dict, cmu_trie) = get_cmudict()?;
+    let frameworks = get_frameworks(&cmu_trie)?;
+    let mut outputs = derived_outputs(&cmu_trie, &frameworks)?;
+    outputs.push(("./output/cmudict.bin".to_owned(), dict));
+    outputs.push(("./output/frameworks.csv".to_owned(), frameworks));
+    for (path, contents) in outputs {
+        fs::write(path, contents)?;
+    }
     Ok(())
 }
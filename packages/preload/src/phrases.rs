@@ -0,0 +1,39 @@
+use anyhow::Result;
+use csv::Writer;
+use radix_trie::Trie;
+use std::fs;
+
+type CmuTrie = Trie<String, String>;
+const PATH: &str = "./tables/phrases.txt";
+
+// the phrases of tables/phrases.txt with their pronunciations, like frameworks.csv
+// without stars. the runtime finds puns among them and the cmudict words
+pub fn get_phrases(trie: &CmuTrie) -> Result<Vec<u8>> {
+    let mut wtr = Writer::from_writer(Vec::new());
+    for phrase in fs::read_to_string(PATH)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    {
+        // cmudict words have no punctuation
+        let pronounces: Option<Vec<&String>> = phrase
+            .split_whitespace()
+            .map(|word| {
+                let word: String = word.chars().filter(char::is_ascii_alphanumeric).collect();
+                trie.get(&word.to_ascii_uppercase())
+            })
+            .collect();
+        match pronounces {
+            Some(pronounces) => {
+                let pronounce = pronounces
+                    .iter()
+                    .map(|pronounce| pronounce.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                wtr.write_record([phrase, &pronounce])?;
+            }
+            None => eprintln!("skipping phrase with words not in cmudict: {}", phrase),
+        }
+    }
+    Ok(wtr.into_inner()?)
+}
@@ -1,5 +1,6 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use csv::ReaderBuilder;
+use itertools::Itertools;
 use std::collections::HashMap;
 
 type PhonemePositions<T> = HashMap<String, (T, T)>;
@@ -49,57 +50,88 @@ fn get_conversion(path: &str) -> Result<Conversion> {
         })
         .collect())
 }
+// where an arpabet vowel or consonant is, and for diphthongs (such as aɪ)
+// the points it glides from and to
+#[derive(Debug, Clone, Copy)]
+struct ArpabetPosition {
+    pos: (f32, f32),
+    glide: Option<((f32, f32), (f32, f32))>,
+}
+impl ArpabetPosition {
+    fn map(self, f: impl Fn((f32, f32)) -> (f32, f32)) -> Self {
+        ArpabetPosition {
+            pos: f(self.pos),
+            glide: self.glide.map(|(start, end)| (f(start), f(end))),
+        }
+    }
+}
+type ArpabetPositions = HashMap<String, ArpabetPosition>;
+
+fn to_f32(pos: &(usize, usize)) -> (f32, f32) {
+    (pos.0 as f32, pos.1 as f32)
+}
 fn phoneme_to_arpabet_positions(
     phoneme_positions: PhonemePositions<usize>,
     arpabet_to_phoneme: Conversion,
-) -> PhonemePositions<usize> {
+    glides: bool,
+) -> ArpabetPositions {
     arpabet_to_phoneme
         .into_iter()
         .map(|(arpabet, phonemes)| {
-            let sum_pos = phonemes
+            let positions: Vec<&(usize, usize)> = phonemes
                 .iter()
                 .map(|phoneme| phoneme_positions.get(phoneme).unwrap())
-                // average the positions if there are multiple phonemes (such as aɪ)
+                .collect();
+            // average the positions if there are multiple phonemes
+            let sum_pos = positions
+                .iter()
                 .fold((0, 0), |acc, pos| (acc.0 + pos.0, acc.1 + pos.1));
-            let average_pos = (sum_pos.0 / phonemes.len(), sum_pos.1 / phonemes.len());
-            (arpabet, average_pos)
+            let len = positions.len() as f32;
+            let average_pos = (sum_pos.0 as f32 / len, sum_pos.1 as f32 / len);
+            // vowels in different cells glide from the first to the last, the ones in
+            // the same cell (such as ɑ ɒ) only differ in rounding. consonants made of
+            // several phonemes (such as tʃ) are said together, not one after another
+            let (first, last) = (positions[0], positions[positions.len() - 1]);
+            let glide = (glides && first != last).then(|| (to_f32(first), to_f32(last)));
+            let position = ArpabetPosition {
+                pos: average_pos,
+                glide,
+            };
+            (arpabet, position)
         })
         .collect()
 }
-fn normalize_phoneme_positions(
-    phoneme_positions: PhonemePositions<usize>,
-) -> PhonemePositions<f32> {
+fn normalize_phoneme_positions(phoneme_positions: ArpabetPositions) -> ArpabetPositions {
     // put all vals between 0 and 1
-    let x_vals = phoneme_positions.iter().map(|(_, pos)| pos.0);
-    let y_vals = phoneme_positions.iter().map(|(_, pos)| pos.1);
-    let x_min = x_vals.clone().min().unwrap();
-    let x_range = x_vals.max().unwrap() - x_min;
-    let y_min = y_vals.clone().min().unwrap();
-    let y_range = y_vals.max().unwrap() - y_min;
+    let x_vals = phoneme_positions.values().map(|position| position.pos.0);
+    let y_vals = phoneme_positions.values().map(|position| position.pos.1);
+    let x_min = x_vals.clone().fold(f32::MAX, f32::min);
+    let x_range = x_vals.fold(f32::MIN, f32::max) - x_min;
+    let y_min = y_vals.clone().fold(f32::MAX, f32::min);
+    let y_range = y_vals.fold(f32::MIN, f32::max) - y_min;
     phoneme_positions
         .into_iter()
-        .map(|(phoneme, pos)| {
-            (
-                phoneme,
-                (
-                    (pos.0 - x_min) as f32 / x_range as f32,
-                    (pos.1 - y_min) as f32 / y_range as f32,
-                ),
-            )
+        .map(|(phoneme, position)| {
+            let position =
+                position.map(|pos| ((pos.0 - x_min) / x_range, (pos.1 - y_min) / y_range));
+            (phoneme, position)
         })
         .collect()
 }
 fn squish_phoneme_positions(
-    phoneme_positions: PhonemePositions<f32>,
+    phoneme_positions: ArpabetPositions,
     squish_factor: f32,
-) -> PhonemePositions<f32> {
+) -> ArpabetPositions {
     phoneme_positions
         .into_iter()
-        .map(|(phoneme, (x, y))| {
-            // x goes from no squish at y = 0 to squish factor at y = 1
-            // and is aligned to the right
-            let squish = 1.0 + (squish_factor - 1.0) * y;
-            (phoneme, ((1.0 - squish) + x * squish, y))
+        .map(|(phoneme, position)| {
+            let position = position.map(|(x, y)| {
+                // x goes from no squish at y = 0 to squish factor at y = 1
+                // and is aligned to the right
+                let squish = 1.0 + (squish_factor - 1.0) * y;
+                ((1.0 - squish) + x * squish, y)
+            });
+            (phoneme, position)
         })
         .collect()
 }
@@ -109,38 +141,185 @@ fn save_arpabet_pronounce(
     pos_path: &str,
     conversion_path: &str,
     div: usize,
-    out_path: String,
     squish_factor: f32,
-) -> Result<()> {
+    glides: bool,
+) -> Result<Vec<u8>> {
     let phoneme_positions = get_phoneme_positions(pos_path, div)?;
     let arpabet_to_phoneme = get_conversion(conversion_path)?;
-    let arpabet_positions = phoneme_to_arpabet_positions(phoneme_positions, arpabet_to_phoneme);
+    let arpabet_positions =
+        phoneme_to_arpabet_positions(phoneme_positions, arpabet_to_phoneme, glides);
     let arpabet_positions = normalize_phoneme_positions(arpabet_positions);
     let arpabet_positions = squish_phoneme_positions(arpabet_positions, squish_factor);
 
-    // save to csv
-    let mut wtr = csv::Writer::from_path(out_path).unwrap();
-    for (arpabet, pos) in arpabet_positions {
-        wtr.write_record(&[arpabet, pos.0.to_string(), pos.1.to_string()])
-            .unwrap();
+    // save to csv, sorted so the output is the same every time.
+    // diphthongs get four more columns: where they glide from and to
+    let mut wtr = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+    for (arpabet, position) in arpabet_positions
+        .into_iter()
+        .sorted_by(|a, b| a.0.cmp(&b.0))
+    {
+        let mut record = vec![
+            arpabet,
+            position.pos.0.to_string(),
+            position.pos.1.to_string(),
+        ];
+        if let Some((start, end)) = position.glide {
+            record.extend([start.0, start.1, end.0, end.1].iter().map(f32::to_string));
+        }
+        wtr.write_record(&record)?;
+    }
+    Ok(wtr.into_inner()?)
+}
+// the values each articulatory feature can have, from 0 to 1 in order. consonants
+// and vowels use different scales for place and manner, but are never compared
+const FEATURE_SCALES: &[&[&[&str]]] = &[
+    // place
+    &[
+        &[
+            "bilabial",
+            "labiodental",
+            "dental",
+            "alveolar",
+            "postalveolar",
+            "palatal",
+            "velar",
+            "glottal",
+        ],
+        &["front", "central", "back"],
+    ],
+    // manner
+    &[
+        &[
+            "stop",
+            "affricate",
+            "fricative",
+            "nasal",
+            "flap",
+            "approximant",
+            "lateral",
+        ],
+        &[
+            "close",
+            "near-close",
+            "close-mid",
+            "mid",
+            "open-mid",
+            "near-open",
+            "open",
+        ],
+    ],
+    &[&["voiceless", "voiced"]],
+    &[&["unrounded", "rounded"]],
+    &[&["oral", "nasal"]],
+    &[&["short", "long"]],
+    // offglide: where a diphthong glides to, so AY and AW differ though they start alike
+    &[&["front", "none", "back"]],
+];
+
+fn feature_value(feature: usize, value: &str) -> Result<f32> {
+    FEATURE_SCALES[feature]
+        .iter()
+        .find_map(|scale| {
+            let index = scale.iter().position(|other| *other == value)?;
+            Some(index as f32 / (scale.len() - 1) as f32)
+        })
+        .ok_or_else(|| anyhow!("unknown articulatory feature value: {}", value))
+}
+
+// turns the named features of each arpabet phone into numbers, sorted by arpabet
+fn save_features(path: &str) -> Result<Vec<u8>> {
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    let records = ReaderBuilder::new()
+        .from_path(path)?
+        .into_records()
+        .collect::<Result<Vec<_>, _>>()?;
+    for record in records.iter().sorted_by_key(|record| record.get(0)) {
+        let mut fields = record.iter();
+        let arpabet = fields.next().unwrap_or_default();
+        let values = fields
+            .enumerate()
+            .map(|(feature, value)| Ok(feature_value(feature, value)?.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        if values.len() != FEATURE_SCALES.len() {
+            return Err(anyhow!("{} has {} features", arpabet, values.len()));
+        }
+        wtr.write_record(std::iter::once(arpabet.to_owned()).chain(values))?;
     }
+    Ok(wtr.into_inner()?)
+}
 
-    Ok(())
+// how each arpabet phone is written in IPA, the usual spelling first. phonemes in the
+// same cell (such as ɑ ɒ) are other spellings, the rest are written together (such as aɪ)
+fn ipa_spellings(pos_path: &str, conversion_path: &str, div: usize) -> Result<Conversion> {
+    let phoneme_positions = get_phoneme_positions(pos_path, div)?;
+    Ok(get_conversion(conversion_path)?
+        .into_iter()
+        .map(|(arpabet, phonemes)| {
+            let same_cell = phonemes
+                .iter()
+                .map(|phoneme| phoneme_positions.get(phoneme))
+                .all_equal();
+            let spellings = if same_cell {
+                phonemes
+            } else {
+                vec![phonemes.concat()]
+            };
+            (arpabet, spellings)
+        })
+        .collect())
 }
-pub fn get_arpabet_pronounce(out_path: &str) -> Result<()> {
-    save_arpabet_pronounce(
-        "./tables/vowels.csv",
-        "./tables/arpabet-vowels.csv",
-        1,
-        format!("{}/vowels.csv", out_path),
-        0.5,
-    )?;
-    save_arpabet_pronounce(
+// r-colored vowels have no cell of their own in the chart, so their usual spellings
+// go before the one of the cell they're in
+const R_COLORED: &[(&str, &[&str])] = &[("ER", &["ɝ", "ɚ"])];
+
+fn save_ipa() -> Result<Vec<u8>> {
+    let mut spellings = ipa_spellings("./tables/vowels.csv", "./tables/arpabet-vowels.csv", 1)?;
+    spellings.extend(ipa_spellings(
         "./tables/consonants.csv",
         "./tables/arpabet-consonants.csv",
         2,
-        format!("{}/consonants.csv", out_path),
-        1.0,
-    )?;
-    Ok(())
+    )?);
+    for (arpabet, r_colored) in R_COLORED {
+        if let Some(spellings) = spellings.get_mut(*arpabet) {
+            spellings.splice(0..0, r_colored.iter().map(|spelling| spelling.to_string()));
+        }
+    }
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    for (arpabet, spellings) in spellings.into_iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
+        wtr.write_record(&[arpabet, spellings.join(" ")])?;
+    }
+    Ok(wtr.into_inner()?)
+}
+
+// the vowel, consonant, feature and IPA csv files, as (file name, contents)
+pub fn get_arpabet_pronounce() -> Result<Vec<(&'static str, Vec<u8>)>> {
+    Ok(vec![
+        (
+            "vowels.csv",
+            save_arpabet_pronounce(
+                "./tables/vowels.csv",
+                "./tables/arpabet-vowels.csv",
+                1,
+                0.5,
+                true,
+            )?,
+        ),
+        (
+            "consonants.csv",
+            save_arpabet_pronounce(
+                "./tables/consonants.csv",
+                "./tables/arpabet-consonants.csv",
+                2,
+                1.0,
+                false,
+            )?,
+        ),
+        (
+            "features.csv",
+            save_features("./tables/arpabet-features.csv")?,
+        ),
+        ("ipa.csv", save_ipa()?),
+    ])
 }
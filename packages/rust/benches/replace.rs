@@ -0,0 +1,110 @@
+//! Benchmarks for loading the data and replacing text.
+//!
+//! Run with `cargo bench`. Set `BENCH_VOCAB` to a number of words, like `BENCH_VOCAB=100000`,
+//! to also benchmark a synthetic vocabulary of that size, and add `--features rayon` to
+//! compare parallel replacement.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::env;
+use vite_wasm_functions::framework_data::read_frameworks;
+use vite_wasm_functions::pronounce::{DistanceConfig, PronounceBuilder};
+use vite_wasm_functions::replacer::{ReplaceMode, Replacer};
+
+static PARAGRAPH: &str = "Whenever I react to a new fact, I try to be calm and view it \
+    with tact. The best nest in the west was built by a bird that knew the next storm \
+    would come fast, so it worked through the night and into the morning light, \
+    gathering sticks, leaves and string until everything was ready for the rain.";
+
+// random words of one to four syllables, each a vowel with up to two consonants on each side
+fn synthetic_vocab(size: usize) -> Vec<(String, String, u64)> {
+    let builder = PronounceBuilder::new();
+    let vowels: Vec<&str> = builder.vowels().collect();
+    let consonants: Vec<&str> = builder.consonants().collect();
+    let mut rng = SmallRng::seed_from_u64(0);
+    (0..size)
+        .map(|i| {
+            let mut phones = Vec::new();
+            for _ in 0..rng.gen_range(1..=4) {
+                for _ in 0..rng.gen_range(0..=2) {
+                    phones.push(consonants[rng.gen_range(0..consonants.len())]);
+                }
+                phones.push(vowels[rng.gen_range(0..vowels.len())]);
+                for _ in 0..rng.gen_range(0..=2) {
+                    phones.push(consonants[rng.gen_range(0..consonants.len())]);
+                }
+            }
+            (
+                format!("Synthetic{}", i),
+                phones.join(" "),
+                rng.gen_range(0..100_000),
+            )
+        })
+        .collect()
+}
+
+fn load(c: &mut Criterion) {
+    c.bench_function("dictionary load", |b| b.iter(PronounceBuilder::new));
+    c.bench_function("framework pronounce building", |b| {
+        b.iter_batched(
+            read_frameworks,
+            |words| Replacer::new(black_box(words)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn distance(c: &mut Criterion) {
+    let builder = PronounceBuilder::new();
+    let distances = builder.distances(&DistanceConfig::default());
+    let word = builder.pronounce("reaction");
+    let framework = builder.pronounce("attraction");
+    // words missing from the dictionary or with other syllable counts skip the comparison
+    assert_eq!(word.syllable_count(), 3);
+    assert_eq!(framework.syllable_count(), 3);
+    c.bench_function("single word distance", |b| {
+        b.iter(|| black_box(&word).distance(black_box(&framework), &distances))
+    });
+}
+
+fn replace(c: &mut Criterion) {
+    let rep = Replacer::new(read_frameworks());
+    c.bench_function("paragraph replace", |b| {
+        b.iter(|| rep.replace(black_box(PARAGRAPH), 0.5, 100, ReplaceMode::Sound))
+    });
+}
+
+fn synthetic(c: &mut Criterion) {
+    let size = match env::var("BENCH_VOCAB") {
+        Ok(size) => size
+            .parse()
+            .expect("BENCH_VOCAB should be a number of words"),
+        Err(_) => return,
+    };
+    let words = synthetic_vocab(size);
+    let mut group = c.benchmark_group(format!("synthetic vocab {}", size));
+    group.sample_size(10);
+    group.bench_function("framework pronounce building", |b| {
+        b.iter_batched(|| words.clone(), Replacer::new, BatchSize::LargeInput)
+    });
+    let rep = Replacer::new(words);
+    group.bench_function("paragraph replace", |b| {
+        b.iter(|| rep.replace(black_box(PARAGRAPH), 0.5, 0, ReplaceMode::Sound))
+    });
+    #[cfg(feature = "rayon")]
+    {
+        let options = vite_wasm_functions::replacer::ReplaceOptions {
+            threshold: 0.5,
+            star_threshold: 0,
+            ..Default::default()
+        };
+        group.bench_function("parallel paragraph replace", |b| {
+            b.iter(|| rep.par_replace_with(black_box(PARAGRAPH), &options))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, load, distance, replace, synthetic);
+criterion_main!(benches);
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+static VOWELS: &str = "../preload/output/pronounce/vowels.csv";
+static CONSONANTS: &str = "../preload/output/pronounce/consonants.csv";
+static FEATURES: &str = "../preload/output/pronounce/features.csv";
+static IPA: &str = "../preload/output/pronounce/ipa.csv";
+
+fn read(path: &str) -> String {
+    println!("cargo:rerun-if-changed={}", path);
+    fs::read_to_string(path).unwrap_or_else(|err| panic!("{}: {}", path, err))
+}
+
+// turns the preload phone tables into a static table sorted by name, so the embedded
+// build looks phones up without parsing anything at startup
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_EMBEDDED").is_none() {
+        return;
+    }
+    let by_name = |csv: String| -> HashMap<String, String> {
+        csv.lines()
+            .filter_map(|line| line.split_once(','))
+            .map(|(name, rest)| (name.to_owned(), rest.to_owned()))
+            .collect()
+    };
+    let features = by_name(read(FEATURES));
+    let ipa = by_name(read(IPA));
+    let mut phones = Vec::new();
+    for (path, vowel) in [(VOWELS, true), (CONSONANTS, false)] {
+        let csv = read(path);
+        for line in csv.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.split(',');
+            let mut next = || fields.next().expect("expected name,x,y").trim();
+            let name = next().to_owned();
+            let x: f32 = next().parse().expect("x is not a number");
+            let y: f32 = next().parse().expect("y is not a number");
+            // diphthongs have where they glide from and to after that
+            let rest: Vec<f32> = fields
+                .map(|field| field.trim().parse().expect("glide is not a number"))
+                .collect();
+            let glide = match rest[..] {
+                [] => "None".to_owned(),
+                [start_x, start_y, end_x, end_y] => format!(
+                    "Some(Glide {{ start: ({:?}, {:?}), end: ({:?}, {:?}) }})",
+                    start_x, start_y, end_x, end_y
+                ),
+                _ => panic!("expected name,x,y or name,x,y,start_x,start_y,end_x,end_y"),
+            };
+            let features = features
+                .get(&name)
+                .unwrap_or_else(|| panic!("no articulatory features for {}", name))
+                .split(',')
+                .map(|value| {
+                    format!(
+                        "{:?}",
+                        value.parse::<f32>().expect("feature is not a number")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let ipa = ipa
+                .get(&name)
+                .unwrap_or_else(|| panic!("no IPA spelling for {}", name))
+                .clone();
+            phones.push((name, vowel, x, y, glide, features, ipa));
+        }
+    }
+    phones.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut out = String::from("pub static PHONES: &[PhoneEntry] = &[\n");
+    for (name, vowel, x, y, glide, features, ipa) in phones {
+        writeln!(
+            out,
+            "    PhoneEntry {{ name: Cow::Borrowed({:?}), vowel: {}, x: {:?}, y: {:?}, glide: {}, features: [{}], ipa: Cow::Borrowed({:?}) }},",
+            name, vowel, x, y, glide, features, ipa
+        )
+        .unwrap();
+    }
+    out.push_str("];\n");
+    let dest = Path::new(&env::var_os("OUT_DIR").unwrap()).join("phones.rs");
+    fs::write(dest, out).unwrap();
+}
@@ -0,0 +1,133 @@
+use crate::replacer::{Edit, ReplaceOptions, Replacement, Replacer, Splice};
+use crate::{to_js_replacement, JsReplacement};
+use js_sys::Array;
+use serde_derive::Serialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+// text that stays replaced while it is edited, so only the words around each
+// edit have to be replaced again, which keeps long texts responsive
+#[wasm_bindgen]
+pub struct Document {
+    rep: Rc<RefCell<Replacer>>,
+    options: ReplaceOptions,
+    text: String,
+    // one per word or gap between words, never merged, so indices stay stable
+    replacements: Vec<Replacement>,
+    // the version of the replacer the replacements were made with
+    version: u64,
+}
+
+#[derive(Serialize)]
+struct JsSplice {
+    start: usize,
+    removed: usize,
+    segments: Vec<JsReplacement>,
+}
+
+// javascript strings are indexed in utf-16 code units, but rust strings in bytes.
+// an offset in the middle of a surrogate pair moves to the start of its character,
+// or with `round_up` to the end of it
+fn utf16_to_byte_offset(s: &str, offset: usize, round_up: bool) -> usize {
+    let mut utf16 = 0;
+    for (byte, c) in s.char_indices() {
+        let next = utf16 + c.len_utf16();
+        if utf16 >= offset || (next > offset && !round_up) {
+            return byte;
+        }
+        utf16 = next;
+    }
+    s.len()
+}
+
+impl Document {
+    pub fn new(rep: Rc<RefCell<Replacer>>, text: &str, options: ReplaceOptions) -> Self {
+        let (replacements, version) = {
+            let replacer = rep.borrow();
+            (replacer.replace_with(text, &options), replacer.version())
+        };
+        Document {
+            rep,
+            options,
+            text: text.to_owned(),
+            replacements,
+            version,
+        }
+    }
+
+    // replaces the whole text again if the replacer changed since the last time
+    fn refresh(&mut self) {
+        let replacer = self.rep.borrow();
+        if replacer.version() != self.version {
+            self.replacements = replacer.replace_with(&self.text, &self.options);
+            self.version = replacer.version();
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Document {
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    // every segment of the document, in order
+    pub fn segments(&mut self) -> Array {
+        self.refresh();
+        self.replacements
+            .iter()
+            .cloned()
+            .map(to_js_replacement)
+            .map(|segment| serde_wasm_bindgen::to_value(&segment).unwrap())
+            .collect()
+    }
+
+    // applies an edit where `deleted` utf-16 code units at `offset` were replaced by
+    // `inserted`, returning `{ start, removed, segments }`: the segments from index
+    // `start` to `start + removed` have to be swapped for the new `segments`. if the
+    // replacer changed since the segments were made, all of them are swapped
+    pub fn edit(
+        &mut self,
+        offset: usize,
+        deleted: usize,
+        inserted: &str,
+    ) -> Result<JsValue, JsValue> {
+        let start = utf16_to_byte_offset(&self.text, offset, false);
+        let end = utf16_to_byte_offset(&self.text, offset + deleted, true);
+        let replacer = self.rep.borrow();
+        let splice = if self.version != replacer.version() {
+            self.text.replace_range(start..end, inserted);
+            self.version = replacer.version();
+            Splice {
+                start: 0,
+                removed: self.replacements.len(),
+                replacements: replacer.replace_with(&self.text, &self.options),
+            }
+        } else {
+            let edit = Edit {
+                offset: start,
+                deleted: end - start,
+                inserted: inserted.to_owned(),
+            };
+            let splice = replacer
+                .replace_edit(&self.replacements, &edit, &self.options)
+                .map_err(|err| JsValue::from_str(&err.to_string()))?;
+            self.text.replace_range(start..end, inserted);
+            splice
+        };
+
+        let js_splice = JsSplice {
+            start: splice.start,
+            removed: splice.removed,
+            segments: splice
+                .replacements
+                .iter()
+                .cloned()
+                .map(to_js_replacement)
+                .collect(),
+        };
+        splice.apply(&mut self.replacements);
+        Ok(serde_wasm_bindgen::to_value(&js_splice).unwrap())
+    }
+}
@@ -1,23 +1,49 @@
+#[cfg(feature = "embedded")]
+use crate::pronounce::Accent;
+use crate::LoadError;
 use csv::ReaderBuilder;
 
+#[cfg(feature = "embedded")]
 static WORDS: &str = include_str!("../../preload/output/frameworks.csv");
+#[cfg(feature = "embedded")]
+static RP_WORDS: &str = include_str!("../../preload/output/frameworks-rp.csv");
 
+#[cfg(feature = "embedded")]
 pub fn read_frameworks() -> Vec<(String, String, u64)> {
+    parse_frameworks(WORDS).expect("the embedded frameworks are valid")
+}
+// the frameworks as they are said in an accent
+#[cfg(feature = "embedded")]
+pub fn read_accent_frameworks(accent: Accent) -> Vec<(String, String, u64)> {
+    let words = match accent {
+        Accent::American => WORDS,
+        Accent::British => RP_WORDS,
+    };
+    parse_frameworks(words).expect("the embedded frameworks are valid")
+}
+
+// parses the frameworks.csv written by preload: name, arpabet and stars
+pub fn parse_frameworks(csv_string: &str) -> Result<Vec<(String, String, u64)>, LoadError> {
     let mut reader = ReaderBuilder::new()
         .has_headers(false)
-        .from_reader(WORDS.as_bytes());
+        .from_reader(csv_string.as_bytes());
     reader
         .records()
         .map(|record| {
-            let record = record.unwrap();
-            let name = record.get(0).unwrap();
-            let pronounce = record.get(1).unwrap();
-            let stars = record.get(2).unwrap();
-            (
-                name.to_owned(),
-                pronounce.to_owned(),
-                stars.parse::<u64>().unwrap(),
-            )
+            let record = record?;
+            match (record.get(0), record.get(1), record.get(2)) {
+                (Some(name), Some(pronounce), Some(stars)) => Ok((
+                    name.to_owned(),
+                    pronounce.to_owned(),
+                    stars.parse::<u64>().map_err(|_| {
+                        LoadError::invalid(format!("{} is not a number of stars", stars))
+                    })?,
+                )),
+                _ => Err(LoadError::invalid(format!(
+                    "expected name,arpabet,stars in {:?}",
+                    record
+                ))),
+            }
         })
         .collect()
 }
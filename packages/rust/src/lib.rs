@@ -1,12 +1,36 @@
-mod framework_data;
-mod pronounce;
-mod replacer;
+mod document;
+pub mod framework_data;
+mod load_error;
+pub mod pronounce;
+pub mod pun;
+pub mod replacer;
+pub use document::Document;
+use framework_data::parse_frameworks;
+#[cfg(feature = "british")]
+use framework_data::read_accent_frameworks;
+#[cfg(feature = "embedded")]
 use framework_data::read_frameworks;
 use itertools::Itertools;
 use js_sys::Array;
+pub use load_error::LoadError;
+#[cfg(feature = "british")]
+use pronounce::Accent;
+use pronounce::DistanceConfig;
+use pronounce::PhoneTables;
+use pronounce::PronounceBuilder;
+use pronounce::SpanishSource;
+use pun::parse_phrases;
+#[cfg(feature = "embedded")]
+use pun::read_phrases;
+use pun::PunFinder;
+use replacer::PartOfSpeech;
+use replacer::ReplaceMode;
+use replacer::ReplaceOptions;
 use replacer::Replacement;
 use replacer::Replacer;
 use serde_derive::Serialize;
+use std::cell::{OnceCell, RefCell};
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -17,50 +41,227 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 #[wasm_bindgen]
 pub struct FrameworkReplacer {
-    rep: Replacer,
+    // shared with the documents made from it
+    rep: Rc<RefCell<Replacer>>,
+    // (phrase, arpabet) to find puns among, along with the dictionary words
+    phrases: Vec<(String, String)>,
+    // made the first time puns are asked for, because indexing every word takes a while
+    puns: OnceCell<PunFinder>,
 }
 
 #[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct JsReplacement {
     pub word: String,
     pub replace: bool,
+    // only set for replaced words
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part_of_speech: Option<PartOfSpeech>,
+}
+
+#[derive(Serialize)]
+struct JsPun<'a> {
+    text: &'a str,
+    distance: f32,
+}
+
+impl FrameworkReplacer {
+    fn from_replacer(replacer: Replacer) -> Self {
+        #[cfg(feature = "embedded")]
+        let phrases = read_phrases();
+        #[cfg(not(feature = "embedded"))]
+        let phrases = Vec::new();
+        FrameworkReplacer {
+            rep: Rc::new(RefCell::new(replacer)),
+            phrases,
+            puns: OnceCell::new(),
+        }
+    }
 }
 
 #[wasm_bindgen]
 impl FrameworkReplacer {
+    #[cfg(feature = "embedded")]
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        let rep = Replacer::new(read_frameworks());
-        FrameworkReplacer { rep }
+        Self::from_replacer(Replacer::new(read_frameworks()))
+    }
+
+    // a replacer for words said in an accent, "american" (like `new`) or "british".
+    // only with the `british` feature
+    #[cfg(feature = "british")]
+    pub fn with_accent(accent: JsValue) -> Result<FrameworkReplacer, JsValue> {
+        let accent: Accent = serde_wasm_bindgen::from_value(accent)?;
+        let replacer = Replacer::with_builder(
+            read_accent_frameworks(accent),
+            PronounceBuilder::with_accent(accent),
+            DistanceConfig::default(),
+        );
+        Ok(Self::from_replacer(replacer))
     }
 
-    pub fn replace(&self, s: &str, threshold: f32, star_threshold: u32) -> Array {
-        self.rep
-            .replace(s, threshold, star_threshold)
+    // makes a replacer from data fetched separately, for builds without the `embedded`
+    // feature: the bytes of cmudict.bin, the text of frameworks.csv, and
+    // `{ vowels, consonants, features, ipa }` with the text of the csv files in preload's output/pronounce
+    pub fn from_bytes(
+        dict: Vec<u8>,
+        vocab: &str,
+        tables: JsValue,
+    ) -> Result<FrameworkReplacer, JsValue> {
+        let tables: PhoneTables = serde_wasm_bindgen::from_value(tables)?;
+        let builder = PronounceBuilder::from_bytes(dict, &tables)?;
+        let vocab = parse_frameworks(vocab)?;
+        let replacer = Replacer::with_builder(vocab, builder, DistanceConfig::default());
+        Ok(Self::from_replacer(replacer))
+    }
+
+    // adds a dictionary whose words are used instead of the ones already there. for
+    // British English without the `british` feature, pass cmudict-rp.bin here (and
+    // frameworks-rp.csv to `from_bytes` without the `embedded` feature)
+    pub fn add_dictionary(&mut self, dict: Vec<u8>) -> Result<(), JsValue> {
+        self.rep.borrow_mut().add_dict(dict)?;
+        self.puns = OnceCell::new();
+        Ok(())
+    }
+
+    // reads words the dictionaries don't have with the spelling rules of a language, so
+    // text in that language can be replaced. only "spanish" for now
+    pub fn add_language(&mut self, language: &str) -> Result<(), JsValue> {
+        match language {
+            "spanish" => self.rep.borrow_mut().add_fallback_source(SpanishSource),
+            other => return Err(JsValue::from_str(&format!("unknown language: {}", other))),
+        }
+        self.puns = OnceCell::new();
+        Ok(())
+    }
+
+    // adds phrases to find puns among, from the text of a csv like preload's phrases.csv.
+    // builds without the `embedded` feature start without any. nothing is added if a
+    // row is malformed or its arpabet can't be read
+    pub fn add_phrases(&mut self, csv: &str) -> Result<(), JsValue> {
+        let phrases = parse_phrases(csv)?;
+        let rep = self.rep.borrow();
+        if let Some((phrase, arpabet)) = phrases
             .iter()
-            .map(|replacement| match replacement {
-                Replacement::Keep(word) => JsReplacement {
-                    word: word.clone(),
-                    replace: false,
-                },
-                Replacement::Replace(word) => JsReplacement {
-                    word: word.clone(),
-                    replace: true,
-                },
-            })
-            .coalesce(|mut part, other_part| {
-                if part.replace == other_part.replace {
-                    part.word.push_str(&other_part.word);
-                    Ok(part)
-                } else {
-                    Err((part, other_part))
-                }
+            .find(|(_, arpabet)| rep.builder().pronounce_from_arpabet(arpabet).is_none())
+        {
+            return Err(JsValue::from_str(&format!(
+                "can't read the arpabet of {}: {}",
+                phrase, arpabet
+            )));
+        }
+        self.phrases.extend(phrases);
+        self.puns = OnceCell::new();
+        Ok(())
+    }
+
+    // up to limit words and phrases that sound like a framework (or any other word)
+    // as `{ text, distance }`, closest first. the first call indexes the dictionary
+    pub fn puns(&self, framework: &str, threshold: f32, limit: usize) -> Array {
+        let rep = self.rep.borrow();
+        let finder = self
+            .puns
+            .get_or_init(|| PunFinder::new(rep.builder(), self.phrases.clone()));
+        rep.puns(finder, framework, threshold, limit)
+            .into_iter()
+            .map(|(distance, text)| {
+                serde_wasm_bindgen::to_value(&JsPun { text, distance }).unwrap()
             })
-            .map(|replacement| serde_wasm_bindgen::to_value(&replacement).unwrap())
             .collect()
     }
+
+    // loads a distance config, such as one emitted by the preload `tune` tool
+    pub fn set_distance_config(&mut self, json: &str) -> Result<(), JsValue> {
+        let config =
+            DistanceConfig::from_json(json).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        self.rep.borrow_mut().set_config(config);
+        Ok(())
+    }
+
+    // how a word is said, in IPA. empty if the word isn't in the dictionary
+    pub fn ipa(&self, word: &str) -> String {
+        let rep = self.rep.borrow();
+        rep.builder().to_ipa(&rep.builder().pronounce(word))
+    }
+
+    // adds an array of words that are skipped when the `stopwords` option is on
+    pub fn add_stopwords(&mut self, words: JsValue) -> Result<(), JsValue> {
+        let words: Vec<String> = serde_wasm_bindgen::from_value(words)?;
+        self.rep.borrow_mut().add_stopwords(words);
+        Ok(())
+    }
+
+    // the mode is "sound", "rhyme" or "alliteration", and "sound" if left out
+    pub fn replace(
+        &self,
+        s: &str,
+        threshold: f32,
+        star_threshold: u32,
+        mode: JsValue,
+    ) -> Result<Array, JsValue> {
+        let mode: Option<ReplaceMode> = serde_wasm_bindgen::from_value(mode)?;
+        let replacements =
+            self.rep
+                .borrow()
+                .replace(s, threshold, star_threshold, mode.unwrap_or_default());
+        Ok(to_js_array(replacements))
+    }
+
+    // like `replace`, but takes an options object such as
+    // `{ threshold: 0.5, starThreshold: 0, starWeight: 0.05, sampling: { temperature: 0.1, seed: 42 },
+    // stopwords: true, protected: { words: ["JavaScript"], patterns: ["[A-Z]+"] },
+    // partsOfSpeech: ["noun", "verb", "adjective"], mode: "rhyme" }`.
+    // the mode is "sound" (the default), "rhyme" or "alliteration"
+    pub fn replace_with_options(&self, s: &str, options: JsValue) -> Result<Array, JsValue> {
+        let options: ReplaceOptions = serde_wasm_bindgen::from_value(options)?;
+        Ok(to_js_array(self.rep.borrow().replace_with(s, &options)))
+    }
+
+    // makes a document that can be edited without replacing all of its text again,
+    // taking the same options as `replace_with_options`
+    pub fn document(&self, text: &str, options: JsValue) -> Result<Document, JsValue> {
+        let options: ReplaceOptions = serde_wasm_bindgen::from_value(options)?;
+        Ok(Document::new(self.rep.clone(), text, options))
+    }
+}
+
+fn to_js_replacement(replacement: Replacement) -> JsReplacement {
+    match replacement {
+        Replacement::Keep(word) => JsReplacement {
+            word,
+            replace: false,
+            original: None,
+            part_of_speech: None,
+        },
+        Replacement::Replace(word, replaced) => JsReplacement {
+            word,
+            replace: true,
+            original: Some(replaced.original),
+            part_of_speech: Some(replaced.part_of_speech),
+        },
+    }
+}
+
+fn to_js_array(replacements: Vec<Replacement>) -> Array {
+    replacements
+        .into_iter()
+        .map(to_js_replacement)
+        // merge kept parts, replaced ones each have their own details
+        .coalesce(|mut part, other_part| {
+            if !part.replace && !other_part.replace {
+                part.word.push_str(&other_part.word);
+                Ok(part)
+            } else {
+                Err((part, other_part))
+            }
+        })
+        .map(|replacement| serde_wasm_bindgen::to_value(&replacement).unwrap())
+        .collect()
 }
 
+#[cfg(feature = "embedded")]
 impl Default for FrameworkReplacer {
     fn default() -> Self {
         Self::new()
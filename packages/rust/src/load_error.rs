@@ -0,0 +1,54 @@
+use std::error::Error;
+use std::fmt;
+use wasm_bindgen::JsValue;
+
+// why a dictionary, a phone table, the frameworks or the phrases couldn't be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    // the text isn't csv
+    Csv(csv::Error),
+    // the dictionary's word index isn't an fst
+    Fst(fst::Error),
+    // it parsed, but what's in it is missing or doesn't fit together
+    InvalidData(String),
+}
+impl LoadError {
+    pub(crate) fn invalid(message: impl Into<String>) -> LoadError {
+        LoadError::InvalidData(message.into())
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Csv(err) => write!(f, "invalid csv: {}", err),
+            LoadError::Fst(err) => write!(f, "invalid dictionary index: {}", err),
+            LoadError::InvalidData(message) => f.write_str(message),
+        }
+    }
+}
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Csv(err) => Some(err),
+            LoadError::Fst(err) => Some(err),
+            LoadError::InvalidData(_) => None,
+        }
+    }
+}
+impl From<csv::Error> for LoadError {
+    fn from(err: csv::Error) -> Self {
+        LoadError::Csv(err)
+    }
+}
+impl From<fst::Error> for LoadError {
+    fn from(err: fst::Error) -> Self {
+        LoadError::Fst(err)
+    }
+}
+// how wasm callers see it, as the message
+impl From<LoadError> for JsValue {
+    fn from(err: LoadError) -> Self {
+        JsValue::from_str(&err.to_string())
+    }
+}
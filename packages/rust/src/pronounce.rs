@@ -1,176 +1,361 @@
-use itertools::Itertools;
-use radix_trie::Trie;
-use std::collections::HashMap;
-
 mod distance;
-use distance::{consonant_distance, silent_consonant_distance, vowel_distance};
+pub use distance::{DistanceConfig, DistanceModel, PhoneDistances};
 
+#[derive(Clone)]
 pub struct Pronounce {
+    // normalized, so consonant and vowel groups alternate and line up with other words:
+    // a silent consonant is added between any two vowels that aren't separated by a consonant,
+    // and at start and end if start/end is vowel
     phones: Vec<Phone>,
     syllables: u32,
+    // how many of the consonants before each vowel start its syllable,
+    // the rest end the syllable before
+    onsets: Vec<u8>,
+    // the stress of each syllable like cmudict's: 0 for none, 1 for primary and 2 for
+    // secondary, or None if the source of the pronunciation doesn't know
+    stress: Vec<Option<u8>>,
 }
 impl Pronounce {
+    // splits consonants between vowels so each syllable starts with one of them. it
+    // can't know which clusters English allows without the builder's phone names, so
+    // "extra" splits as ɛkst-ɹʌ here but ɛk-stɹʌ with `PronounceBuilder::from_phones`
     pub fn new(phones: Vec<Phone>) -> Pronounce {
-        let syllables = phones.iter().filter(|phone| phone.is_vowel()).count() as u32;
-        Pronounce { phones, syllables }
-    }
-    pub fn empty() -> Pronounce {
-        Pronounce {
-            phones: Vec::new(),
-            syllables: 0,
-        }
+        let phones = phones.into_iter().map(|phone| (phone, None)).collect();
+        Self::syllabified(phones, |cluster| cluster.len().min(1))
     }
-    // adds a silent consonant between any two vowels that aren't separated by a consonant,
-    // and adds a silent consonant at start and end if start/end is vowel, to make it easier to compare
-    fn to_normal(&self) -> Self {
-        let mut normal_phones: Vec<Phone> = self
-            .phones
-            .clone()
-            .into_iter()
-            // add silent consonant between any two vowels that aren't separated by a consonant
-            .group_by(|phone| phone.is_vowel())
-            .into_iter()
-            .flat_map(|(is_vowel, group)| {
-                if is_vowel {
-                    Itertools::intersperse_with(group, || Phone::Silent).collect::<Vec<_>>()
+    // takes each phone with its stress. onset_len is how many of the consonants
+    // between two vowels start the second syllable
+    fn syllabified(
+        stressed_phones: Vec<(Phone, Option<u8>)>,
+        onset_len: impl Fn(&[Phone]) -> usize,
+    ) -> Pronounce {
+        let (phones, stresses): (Vec<Phone>, Vec<Option<u8>>) = stressed_phones.into_iter().unzip();
+        let mut onsets = Vec::new();
+        let mut stress = Vec::new();
+        let mut cluster_start = 0;
+        for (i, phone) in phones.iter().enumerate() {
+            if phone.is_vowel() {
+                stress.push(stresses[i]);
+                let cluster = &phones[cluster_start..i];
+                // the consonants at the start of a word always start its first syllable
+                let onset = if onsets.is_empty() {
+                    cluster.len()
                 } else {
-                    group.collect::<Vec<_>>()
-                }
-            })
-            .collect();
-
-        // add silent consonant at start and end if start/end is vowel
-        if normal_phones[0].is_vowel() {
-            normal_phones.insert(0, Phone::Silent);
+                    onset_len(cluster)
+                };
+                onsets.push(onset as u8);
+                cluster_start = i + 1;
+            }
         }
-        if normal_phones
-            .last()
-            .map(|phone| phone.is_vowel())
-            .unwrap_or(false)
-        {
+        let syllables = onsets.len() as u32;
+        let mut normal_phones = Vec::with_capacity(phones.len() + 2);
+        for phone in phones {
+            if phone.is_vowel() && normal_phones.last().is_none_or(Phone::is_vowel) {
+                normal_phones.push(Phone::Silent);
+            }
+            normal_phones.push(phone);
+        }
+        if normal_phones.last().is_some_and(Phone::is_vowel) {
             normal_phones.push(Phone::Silent);
         }
-        // syllables should be the same as before
-        Self {
+        Pronounce {
             phones: normal_phones,
-            syllables: self.syllables,
+            syllables,
+            onsets,
+            stress,
+        }
+    }
+    pub fn syllable_count(&self) -> u32 {
+        self.syllables
+    }
+    pub fn empty() -> Pronounce {
+        Pronounce {
+            phones: Vec::new(),
+            syllables: 0,
+            onsets: Vec::new(),
+            stress: Vec::new(),
         }
     }
-    fn get_grouped(&self) -> Vec<Vec<Phone>> {
+    // the phones of the word, without the silent consonants added to compare it
+    pub fn phones(&self) -> impl Iterator<Item = Phone> + '_ {
         self.phones
             .iter()
-            .cloned()
-            .group_by(|phone| phone.is_vowel())
-            .into_iter()
-            .map(|(_, group)| group.collect::<Vec<_>>())
-            .collect()
+            .copied()
+            .filter(|phone| *phone != Phone::Silent)
+    }
+    fn get_grouped(&self) -> impl Iterator<Item = &[Phone]> {
+        grouped(&self.phones)
+    }
+    // the phones from the last stressed vowel to the end, which is what rhymes, and
+    // how many syllables that is. without any stress it is the last syllable
+    fn rhyme(&self) -> (&[Phone], u32) {
+        let Some(last) = self.stress.len().checked_sub(1) else {
+            return (&[], 0);
+        };
+        let stressed = self
+            .stress
+            .iter()
+            .rposition(|stress| matches!(stress, Some(1 | 2)))
+            .unwrap_or(last);
+        let start = self
+            .phones
+            .iter()
+            .enumerate()
+            .filter(|(_, phone)| phone.is_vowel())
+            .nth(stressed)
+            .map_or(0, |(i, _)| i);
+        (&self.phones[start..], self.syllables - stressed as u32)
+    }
+    // the consonants the word starts with and its first vowel, which is what alliterates
+    fn head(&self) -> (&[Phone], u32) {
+        match self.phones.iter().position(Phone::is_vowel) {
+            Some(vowel) => (&self.phones[..=vowel], 1),
+            None => (&[], 0),
+        }
     }
     // finds the distance of every vowel/consonant pair in the two pronunciations
     // and sums them up
-    pub fn distance(&self, other: &Pronounce) -> f32 {
-        if self.syllables != other.syllables || self.syllables == 0 {
-            return f32::MAX;
-        }
-        let parts = self.to_normal().get_grouped();
-        let other_parts = other.to_normal().get_grouped();
+    pub fn distance(&self, other: &Pronounce, distances: &PhoneDistances) -> f32 {
+        grouped_distance(
+            (&self.phones, self.syllables),
+            (&other.phones, other.syllables),
+            distances,
+        )
+    }
+    // like `distance`, but only compares what rhymes, so "view" and "Vue" or
+    // "tact" and "React" are close
+    pub fn rhyme_distance(&self, other: &Pronounce, distances: &PhoneDistances) -> f32 {
+        grouped_distance(self.rhyme(), other.rhyme(), distances)
+    }
+    // like `distance`, but only compares the consonants before the first vowel and
+    // that vowel, so "angry" and Angular are close however long they are
+    pub fn alliteration_distance(&self, other: &Pronounce, distances: &PhoneDistances) -> f32 {
+        grouped_distance(self.head(), other.head(), distances)
+    }
+}
 
-        parts
-            .iter()
-            .zip(other_parts.iter())
-            .map(|(group, other_group)| {
-                // go from first to last phoneme in each group at the same speed, comparing each pair
-                let total_index = (group.len() * other_group.len()).clamp(0, 32);
-                let mut len_ratio = group.len() as f32 / other_group.len() as f32;
-                if len_ratio < 1.0 {
-                    len_ratio = 1.0 / len_ratio;
-                }
+fn grouped(phones: &[Phone]) -> impl Iterator<Item = &[Phone]> {
+    phones.chunk_by(|phone, other| phone.is_vowel() == other.is_vowel())
+}
 
-                (0..total_index)
-                    .map(|i| {
-                        let phoneme = &group[i % group.len()];
-                        let other_phoneme = &other_group[i % other_group.len()];
-                        phoneme.distance(other_phoneme)
-                    })
-                    .sum::<f32>()
-                    / total_index as f32
-                    * len_ratio
-            })
-            .sum::<f32>()
+// the distance of two normalized runs of phones with their syllable counts
+fn grouped_distance(
+    (phones, syllables): (&[Phone], u32),
+    (other_phones, other_syllables): (&[Phone], u32),
+    distances: &PhoneDistances,
+) -> f32 {
+    if syllables != other_syllables || syllables == 0 {
+        return f32::MAX;
     }
+
+    grouped(phones)
+        .zip(grouped(other_phones))
+        .map(|(group, other_group)| {
+            // go from first to last phoneme in each group at the same speed, comparing each pair
+            let total_index = (group.len() * other_group.len()).clamp(0, 32);
+            // always divide the longer by the shorter, because inverting
+            // a ratio isn't exact in floating point and breaks symmetry
+            let len_ratio = group.len().max(other_group.len()) as f32
+                / group.len().min(other_group.len()) as f32;
+
+            (0..total_index)
+                .map(|i| {
+                    let phoneme = group[i % group.len()];
+                    let other_phoneme = other_group[i % other_group.len()];
+                    distances.get(phoneme, other_phoneme)
+                })
+                .sum::<f32>()
+                / total_index as f32
+                * len_ratio
+        })
+        .sum::<f32>()
 }
 
-#[derive(Debug, Clone)]
+// a phone as its index in the phone table of the `PronounceBuilder` that made it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Phone {
-    Vowel(f32, f32),
-    Consonant(f32, f32),
+    Vowel(u8),
+    Consonant(u8),
     Silent,
 }
 impl Phone {
-    fn distance(&self, other: &Phone) -> f32 {
-        match (self, other) {
-            (Phone::Vowel(x_1, y_1), Phone::Vowel(x_2, y_2)) => vowel_distance(x_1, x_2, y_1, y_2),
-            (Phone::Consonant(x_1, y_1), Phone::Consonant(x_2, y_2)) => {
-                consonant_distance(x_1, x_2, y_1, y_2)
-            }
-            (Phone::Silent, Phone::Silent) => 0.0,
-            (Phone::Silent, Phone::Consonant(_, y)) | (Phone::Consonant(_, y), Phone::Silent) => {
-                silent_consonant_distance(y)
-            }
-            _ => panic!("tried to compare vowel and consonant"),
-        }
-    }
-
     fn is_vowel(&self) -> bool {
         matches!(self, Phone::Vowel { .. })
     }
 }
 mod cmudict_data;
+#[cfg(feature = "embedded")]
 use cmudict_data::read_cmudict;
+#[cfg(feature = "british")]
+use cmudict_data::read_rp_cmudict;
+use cmudict_data::Dict;
 mod pronounce_data;
+#[cfg(feature = "embedded")]
 use pronounce_data::read_pronounce;
+pub use pronounce_data::PhoneTables;
+use pronounce_data::{parse_pronounce, PhoneEntry};
+mod ipa;
+mod source;
+use source::DictSource;
+pub use source::PronunciationSource;
+mod spanish;
+pub use spanish::SpanishSource;
+mod syllable;
+pub use syllable::Syllable;
+
+use crate::LoadError;
+use serde_derive::Deserialize;
+use std::borrow::Cow;
+
+// which English the words are said in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Accent {
+    // General American, which cmudict is written in
+    #[default]
+    American,
+    // Received Pronunciation, from rules preload applies to cmudict
+    British,
+}
 
 pub struct PronounceBuilder {
-    dict: Trie<String, String>,
-    vowel_map: HashMap<String, (f32, f32)>,
-    consonant_map: HashMap<String, (f32, f32)>,
+    // looked up in order until one knows the word. an accent's dictionary only has
+    // the words it changes, so it goes before the one it changes, and spelling rules
+    // go after every dictionary
+    sources: Vec<Box<dyn PronunciationSource>>,
+    // sorted by name, so phones can be found by binary search
+    phones: Cow<'static, [PhoneEntry]>,
+    // every IPA spelling of every phone, longest first so they can be matched greedily
+    ipa_spellings: Vec<(String, Phone)>,
 }
 impl PronounceBuilder {
+    #[cfg(feature = "embedded")]
     pub fn new() -> PronounceBuilder {
-        let (vowel_map, consonant_map) = read_pronounce();
-        PronounceBuilder {
-            dict: read_cmudict(),
-            vowel_map,
-            consonant_map,
+        Self::from_parts(read_cmudict(), read_pronounce()).expect("Failed to match cmudict phones")
+    }
+    #[cfg(feature = "british")]
+    pub fn with_accent(accent: Accent) -> PronounceBuilder {
+        let mut builder = Self::new();
+        if accent == Accent::British {
+            builder
+                .push_dict(read_rp_cmudict())
+                .expect("Failed to match British cmudict phones");
         }
+        builder
     }
-    fn phone_from_arpabet(&self, arpabet: &str) -> Phone {
-        self.vowel_map
-            .get(arpabet)
-            .map(|(x, y)| Phone::Vowel(*x, *y))
-            .or_else(|| {
-                self.consonant_map
-                    .get(arpabet)
-                    .map(|(x, y)| Phone::Consonant(*x, *y))
-            })
-            .unwrap_or_else(|| {
-                panic!("Could not find phone for arpabet: {}", arpabet);
-            })
+    // builds from the preload outputs instead of the embedded ones: cmudict.bin
+    // and the tables in pronounce/
+    pub fn from_bytes(dict: Vec<u8>, tables: &PhoneTables) -> Result<PronounceBuilder, LoadError> {
+        Self::from_parts(Dict::new(Cow::Owned(dict))?, parse_pronounce(tables)?)
+    }
+    fn from_parts(
+        dict: Dict,
+        phones: Cow<'static, [PhoneEntry]>,
+    ) -> Result<PronounceBuilder, LoadError> {
+        if phones.len() >= u8::MAX as usize {
+            return Err(LoadError::invalid(
+                "too many phones for their ids to fit in a byte",
+            ));
+        }
+        let mut builder = PronounceBuilder {
+            sources: Vec::new(),
+            phones,
+            ipa_spellings: Vec::new(),
+        };
+        builder.ipa_spellings = builder.get_ipa_spellings();
+        builder.push_dict(dict)?;
+        Ok(builder)
+    }
+    fn push_dict(&mut self, dict: Dict) -> Result<(), LoadError> {
+        let source = DictSource::new(dict, self)?;
+        self.add_source(source);
+        Ok(())
+    }
+    // adds a source whose pronunciations are used instead of the ones already there
+    pub fn add_source(&mut self, source: impl PronunciationSource + 'static) {
+        self.sources.insert(0, Box::new(source));
+    }
+    // adds a source that is only asked about words no other source knows, such as the
+    // spelling rules of another language, which would otherwise read English words too
+    pub fn add_fallback_source(&mut self, source: impl PronunciationSource + 'static) {
+        self.sources.push(Box::new(source));
+    }
+    // adds a dictionary in the cmudict.bin format whose words are used instead of
+    // the ones already there, such as preload's cmudict-rp.bin for British English
+    pub fn add_dict(&mut self, dict: Vec<u8>) -> Result<(), LoadError> {
+        self.push_dict(Dict::new(Cow::Owned(dict))?)
+    }
+    // every arpabet vowel and consonant this builder knows about
+    pub fn vowels(&self) -> impl Iterator<Item = &str> {
+        self.phones
+            .iter()
+            .filter(|phone| phone.vowel)
+            .map(|phone| phone.name.as_ref())
+    }
+    pub fn consonants(&self) -> impl Iterator<Item = &str> {
+        self.phones
+            .iter()
+            .filter(|phone| !phone.vowel)
+            .map(|phone| phone.name.as_ref())
+    }
+    // the distance between every two phones of this builder, to compare its pronunciations
+    pub fn distances(&self, config: &DistanceConfig) -> PhoneDistances {
+        PhoneDistances::new(&self.phones, config)
+    }
+    fn phone_at(&self, index: usize) -> Phone {
+        if self.phones[index].vowel {
+            Phone::Vowel(index as u8)
+        } else {
+            Phone::Consonant(index as u8)
+        }
+    }
+    // the phone of an arpabet name without stress, such as "AE"
+    pub fn phone_from_arpabet(&self, arpabet: &str) -> Option<Phone> {
+        let index = self
+            .phones
+            .binary_search_by(|phone| phone.name.as_ref().cmp(arpabet))
+            .ok()?;
+        Some(self.phone_at(index))
     }
-    pub fn pronounce_from_arpabet(&self, arpabet: &str) -> Pronounce {
+    // also takes IPA, such as "ɹiækt", which is anything that isn't phone names.
+    // None if it has a phone this builder doesn't know
+    pub fn pronounce_from_arpabet(&self, arpabet: &str) -> Option<Pronounce> {
+        if !self.is_arpabet(arpabet) {
+            return Some(self.syllabify(self.phones_from_ipa(arpabet)?));
+        }
         let phones = arpabet
             .split_whitespace()
-            // we don't care about stress
-            .map(|part| part.trim_end_matches(|c: char| !c.is_ascii_alphabetic()))
-            .map(|part| self.phone_from_arpabet(part))
-            .collect();
+            .map(|part| {
+                let name = part.trim_end_matches(|c: char| !c.is_ascii_alphabetic());
+                let phone = self.phone_from_arpabet(&name.to_ascii_uppercase())?;
+                Some((phone, part[name.len()..].parse().ok()))
+            })
+            .collect::<Option<_>>()?;
 
-        Pronounce::new(phones)
+        Some(self.syllabify(phones))
+    }
+    // every word of every source that can list them, sorted and without duplicates
+    pub fn words(&self) -> Vec<String> {
+        let mut words: Vec<String> = self
+            .sources
+            .iter()
+            .flat_map(|source| source.words())
+            .collect();
+        words.sort_unstable();
+        words.dedup();
+        words
     }
     pub fn pronounce(&self, word: &str) -> Pronounce {
-        self.dict
-            .get(&word.to_ascii_uppercase())
-            .map(|s| s.to_string())
-            .map(|arpabet| self.pronounce_from_arpabet(&arpabet))
+        self.sources
+            .iter()
+            .find_map(|source| source.stressed_phones(word, self))
+            .map(|phones| self.syllabify(phones))
             .unwrap_or_else(Pronounce::empty)
     }
 }
+#[cfg(feature = "embedded")]
+impl Default for PronounceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
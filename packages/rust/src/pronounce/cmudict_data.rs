@@ -1,23 +1,103 @@
-use flate2::read::GzDecoder;
-use radix_trie::Trie;
-use std::io::Read;
+use crate::LoadError;
+use fst::{Map, Streamer};
+use std::borrow::Cow;
 
-static DICT: &[u8] = include_bytes!("../../../preload/output/cmudict.gz");
+#[cfg(feature = "embedded")]
+static DICT: &[u8] = include_bytes!("../../../preload/output/cmudict.bin");
+// only the words that are said differently in British English
+#[cfg(feature = "british")]
+static RP_DICT: &[u8] = include_bytes!("../../../preload/output/cmudict-rp.bin");
 
-pub fn read_cmudict() -> Trie<String, String> {
-    let mut decoder = GzDecoder::new(DICT);
-    let mut s = String::new();
-    decoder
-        .read_to_string(&mut s)
-        .expect("Failed to read cmudict");
-    s.lines()
-        .filter(|line| !line.starts_with(";;;"))
-        .map(|line| {
-            let mut parts = line.split("  ");
-            (
-                parts.next().unwrap().to_owned(),
-                parts.next().unwrap().to_owned(),
-            )
+// the dictionary written by the preload `encode_dict`, looked up straight from its bytes
+pub struct Dict {
+    phones: Vec<String>,
+    map: Map<Cow<'static, [u8]>>,
+    pronounces: Cow<'static, [u8]>,
+}
+
+impl Dict {
+    pub fn new(data: Cow<'static, [u8]>) -> Result<Dict, LoadError> {
+        let truncated = || LoadError::invalid("dictionary is truncated");
+        if !data.starts_with(b"FSD1") {
+            return Err(LoadError::invalid("dictionary does not start with FSD1"));
+        }
+        let mut offset = 4;
+        let phone_count = *data.get(offset).ok_or_else(truncated)?;
+        offset += 1;
+        let mut phones = Vec::with_capacity(phone_count as usize);
+        for _ in 0..phone_count {
+            let len = *data.get(offset).ok_or_else(truncated)? as usize;
+            let name = data
+                .get(offset + 1..offset + 1 + len)
+                .ok_or_else(truncated)?;
+            phones.push(String::from_utf8_lossy(name).into_owned());
+            offset += 1 + len;
+        }
+        let map_len = data
+            .get(offset..offset + 4)
+            .ok_or_else(truncated)?
+            .iter()
+            .rev()
+            .fold(0, |len, byte| len << 8 | *byte as usize);
+        offset += 4;
+        if data.len() < offset + map_len {
+            return Err(truncated());
+        }
+        let end = offset + map_len;
+        // split the data without copying it, the embedded dictionary is only ever borrowed
+        let (map, pronounces) = match data {
+            Cow::Borrowed(data) => (
+                Cow::Borrowed(&data[offset..end]),
+                Cow::Borrowed(&data[end..]),
+            ),
+            Cow::Owned(mut data) => {
+                let pronounces = data.split_off(end);
+                data.drain(..offset);
+                (Cow::Owned(data), Cow::Owned(pronounces))
+            }
+        };
+        Ok(Dict {
+            phones,
+            map: Map::new(map)?,
+            pronounces,
         })
-        .collect()
+    }
+
+    // the phone names the dictionary uses, indexed like the phones from `get`
+    pub fn phone_names(&self) -> &[String] {
+        &self.phones
+    }
+
+    // every word in the dictionary, in order
+    pub fn words(&self) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut stream = self.map.keys();
+        while let Some(word) = stream.next() {
+            words.push(String::from_utf8_lossy(word).into_owned());
+        }
+        words
+    }
+
+    // the (phone index, stress) pairs of an uppercase word, without allocating
+    pub fn get(&self, word: &str) -> Option<impl Iterator<Item = (usize, Option<u8>)> + '_> {
+        let offset = self.map.get(word)? as usize;
+        let len = *self.pronounces.get(offset)? as usize;
+        let encoded = self.pronounces.get(offset + 1..offset + 1 + len)?;
+        Some(encoded.iter().map(|byte| {
+            let stress = match byte >> 6 {
+                0 => None,
+                stress => Some(stress - 1),
+            };
+            ((byte & 0x3f) as usize, stress)
+        }))
+    }
+}
+
+#[cfg(feature = "embedded")]
+pub fn read_cmudict() -> Dict {
+    Dict::new(Cow::Borrowed(DICT)).expect("Failed to read cmudict")
+}
+#[cfg(feature = "british")]
+pub fn read_rp_cmudict() -> Dict {
+    Dict::new(Cow::Borrowed(RP_DICT)).expect("Failed to read British cmudict")
 }
@@ -1,29 +1,204 @@
-const VOWEL_WEIGHT: f32 = 2.0;
-const CONSONANT_WEIGHT: f32 = 0.4;
+use super::pronounce_data::PhoneEntry;
+use super::Phone;
+use serde_derive::{Deserialize, Serialize};
 
-pub fn vowel_distance(x_1: &f32, x_2: &f32, y_1: &f32, y_2: &f32) -> f32 {
+// how phones are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DistanceModel {
+    // the distance between where phones are in the IPA charts
+    Coordinates,
+    // the weighted differences of their articulatory features, which
+    // unlike the charts tell voiced and voiceless consonants apart
+    Features,
+}
+
+// all the knobs of the distance functions, so they can be tuned against human judgement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistanceConfig {
+    pub model: DistanceModel,
+    pub vowel_weight: f32,
+    pub consonant_weight: f32,
+    // added when two vowels differ in both frontness and height
+    pub vowel_imperfect_punish: f32,
+    // added when two consonants differ in manner (row) or place (column)
+    pub consonant_row_punish: f32,
+    pub consonant_col_punish: f32,
+    pub consonant_scale: f32,
+    // base cost of comparing a consonant against a silent consonant
+    pub silent_punish: f32,
+    // how much comparing where vowels glide from and to counts, against only
+    // comparing their middles, when one of them is a diphthong
+    pub glide_weight: f32,
+    // how much each articulatory feature counts with `DistanceModel::Features`
+    pub place_weight: f32,
+    pub manner_weight: f32,
+    pub voicing_weight: f32,
+    pub rounding_weight: f32,
+    pub nasality_weight: f32,
+    pub length_weight: f32,
+    pub offglide_weight: f32,
+    // feature distances go from 0 to 1, this brings them in line with coordinate
+    // distances so the same thresholds work for both models
+    pub feature_scale: f32,
+}
+impl Default for DistanceConfig {
+    fn default() -> Self {
+        DistanceConfig {
+            model: DistanceModel::Coordinates,
+            vowel_weight: 2.0,
+            consonant_weight: 0.4,
+            vowel_imperfect_punish: 0.1,
+            consonant_row_punish: 0.5,
+            consonant_col_punish: 0.1,
+            consonant_scale: 1.6,
+            silent_punish: 0.15,
+            glide_weight: 0.5,
+            place_weight: 1.0,
+            manner_weight: 1.0,
+            voicing_weight: 0.5,
+            rounding_weight: 0.3,
+            nasality_weight: 0.5,
+            length_weight: 0.2,
+            offglide_weight: 0.5,
+            feature_scale: 2.5,
+        }
+    }
+}
+impl DistanceConfig {
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+pub fn vowel_distance(x_1: &f32, x_2: &f32, y_1: &f32, y_2: &f32, config: &DistanceConfig) -> f32 {
     let x_diff = (x_1 - x_2).abs();
     let y_diff = (y_1 - y_2).abs();
     let imperfect_punish = if x_diff != 0.0 && y_diff != 0.0 {
-        0.1
+        config.vowel_imperfect_punish
     } else {
         0.0
     };
-    ((x_diff.powf(2.0) + y_diff.powf(2.0)).sqrt() + imperfect_punish) * VOWEL_WEIGHT
+    ((x_diff.powf(2.0) + y_diff.powf(2.0)).sqrt() + imperfect_punish) * config.vowel_weight
 }
 
-pub fn consonant_distance(x_1: &f32, x_2: &f32, y_1: &f32, y_2: &f32) -> f32 {
+// a vowel that isn't a diphthong starts and ends in the same place
+fn glide_ends(phone: &PhoneEntry) -> ((f32, f32), (f32, f32)) {
+    match phone.glide {
+        Some(glide) => (glide.start, glide.end),
+        None => ((phone.x, phone.y), (phone.x, phone.y)),
+    }
+}
+
+pub fn glide_distance(a: &PhoneEntry, b: &PhoneEntry, config: &DistanceConfig) -> f32 {
+    let middle = vowel_distance(&a.x, &b.x, &a.y, &b.y, config);
+    if a.glide.is_none() && b.glide.is_none() {
+        return middle;
+    }
+    let ((a_start, a_end), (b_start, b_end)) = (glide_ends(a), glide_ends(b));
+    let start = vowel_distance(&a_start.0, &b_start.0, &a_start.1, &b_start.1, config);
+    let end = vowel_distance(&a_end.0, &b_end.0, &a_end.1, &b_end.1, config);
+    (1.0 - config.glide_weight) * middle + config.glide_weight * (start + end) / 2.0
+}
+
+// the weighted average difference of two feature vectors, scaled by `feature_scale`
+pub fn feature_distance(a: &PhoneEntry, b: &PhoneEntry, config: &DistanceConfig) -> f32 {
+    let weights = [
+        config.place_weight,
+        config.manner_weight,
+        config.voicing_weight,
+        config.rounding_weight,
+        config.nasality_weight,
+        config.length_weight,
+        config.offglide_weight,
+    ];
+    let difference: f32 = (a.features.iter().zip(b.features.iter()))
+        .zip(weights.iter())
+        .map(|((a, b), weight)| (a - b).abs() * weight)
+        .sum();
+    difference / weights.iter().sum::<f32>() * config.feature_scale
+}
+
+pub fn consonant_distance(
+    x_1: &f32,
+    x_2: &f32,
+    y_1: &f32,
+    y_2: &f32,
+    config: &DistanceConfig,
+) -> f32 {
     let x_diff = (x_1 - x_2).abs();
     let y_diff = (y_1 - y_2).abs();
-    let row_punish = if y_diff == 0.0 { 0.0 } else { 0.5 };
-    let col_punish = if x_diff == 0.0 { 0.0 } else { 0.1 };
-    ((x_diff.powf(2.0) + y_diff.powf(2.0)).sqrt() + row_punish + col_punish) / 1.6
-        * CONSONANT_WEIGHT
+    let row_punish = if y_diff == 0.0 {
+        0.0
+    } else {
+        config.consonant_row_punish
+    };
+    let col_punish = if x_diff == 0.0 {
+        0.0
+    } else {
+        config.consonant_col_punish
+    };
+    ((x_diff.powf(2.0) + y_diff.powf(2.0)).sqrt() + row_punish + col_punish)
+        / config.consonant_scale
+        * config.consonant_weight
 }
 
-pub fn silent_consonant_distance(y: &f32) -> f32 {
+pub fn silent_consonant_distance(y: &f32, config: &DistanceConfig) -> f32 {
     // the close to 0.5 the more silent it is, because that's where fricatives are
     // the more silent it is, the less the distance
     let consonant_strength = 2.0 * (y - 0.5).abs();
-    consonant_strength * CONSONANT_WEIGHT + 0.15
+    consonant_strength * config.consonant_weight + config.silent_punish
+}
+
+// the distance between every two phones of a phone table for one config, so comparing
+// pronunciations is only lookups. the last row and column are for the silent consonant
+#[derive(Debug, Clone)]
+pub struct PhoneDistances {
+    size: usize,
+    values: Vec<f32>,
+}
+impl PhoneDistances {
+    pub(super) fn new(phones: &[PhoneEntry], config: &DistanceConfig) -> Self {
+        let size = phones.len() + 1;
+        let mut values = Vec::with_capacity(size * size);
+        for i in 0..size {
+            for j in 0..size {
+                values.push(match (phones.get(i), phones.get(j)) {
+                    (Some(a), Some(b)) if a.vowel == b.vowel => match config.model {
+                        DistanceModel::Coordinates if a.vowel => glide_distance(a, b, config),
+                        DistanceModel::Coordinates => {
+                            consonant_distance(&a.x, &b.x, &a.y, &b.y, config)
+                        }
+                        DistanceModel::Features if a.vowel => {
+                            feature_distance(a, b, config) * config.vowel_weight
+                        }
+                        DistanceModel::Features => {
+                            feature_distance(a, b, config) * config.consonant_weight
+                        }
+                    },
+                    (None, None) => 0.0,
+                    // both models compare silence by how strong the consonant is in its chart
+                    (Some(consonant), None) | (None, Some(consonant)) if !consonant.vowel => {
+                        silent_consonant_distance(&consonant.y, config)
+                    }
+                    // vowels never line up with consonants in normalized pronunciations
+                    _ => f32::INFINITY,
+                });
+            }
+        }
+        PhoneDistances { size, values }
+    }
+    fn index(&self, phone: Phone) -> usize {
+        match phone {
+            Phone::Vowel(index) | Phone::Consonant(index) => index as usize,
+            Phone::Silent => self.size - 1,
+        }
+    }
+    pub fn get(&self, phone: Phone, other: Phone) -> f32 {
+        self.values[self.index(phone) * self.size + self.index(other)]
+    }
 }
@@ -0,0 +1,108 @@
+use super::{Phone, Pronounce, PronounceBuilder};
+use std::cmp::Reverse;
+
+// marks that don't change which phones are said: length, syllable breaks,
+// tie bars and the slashes or brackets around a transcription
+const IGNORED: &[char] = &['ː', 'ˑ', '.', '/', '[', ']', '‿', '\u{361}', '\u{35c}'];
+// primary and secondary stress, which come before the stressed syllable
+const STRESS_MARKS: &[(char, u8)] = &[('ˈ', 1), ('ˌ', 2)];
+// common spellings that aren't the ones in the charts
+const ALIASES: &[(char, &str)] = &[('g', "ɡ"), ('r', "ɹ"), ('ɐ', "ʌ"), ('ɫ', "l")];
+// vowels without a chart cell of their own, written like the start of an arpabet
+// diphthong (such as "e" of EY, eɪ). longer spellings are matched first, so "eɪ" is
+// still one phone
+const VOWEL_ALIASES: &[(&str, &str)] = &[("e", "EY"), ("o", "OW"), ("a", "AA")];
+
+impl PronounceBuilder {
+    // arpabet is phone names with stress digits in any case, such as "r iy0 ae1 k t",
+    // anything else is IPA
+    pub(super) fn is_arpabet(&self, s: &str) -> bool {
+        s.split_whitespace().all(|part| {
+            let name = part.trim_end_matches(|c: char| c.is_ascii_digit());
+            self.phone_from_arpabet(&name.to_ascii_uppercase())
+                .is_some()
+        })
+    }
+
+    pub(super) fn get_ipa_spellings(&self) -> Vec<(String, Phone)> {
+        let mut spellings: Vec<(String, Phone, usize)> = self
+            .phones
+            .iter()
+            .enumerate()
+            .flat_map(|(index, entry)| {
+                entry.ipa.split_whitespace().map(move |spelling| {
+                    (spelling.to_owned(), self.phone_at(index), entry.name.len())
+                })
+            })
+            .chain(VOWEL_ALIASES.iter().filter_map(|(spelling, name)| {
+                let phone = self.phone_from_arpabet(name)?;
+                // spellings from the tables win over these
+                Some((spelling.to_string(), phone, usize::MAX))
+            }))
+            .collect();
+        // syllabic and r-colored phones (such as EL and AXR) are spelled like the
+        // plain ones, which have shorter names and win
+        spellings.sort_by_key(|(spelling, _, name_len)| {
+            (Reverse(spelling.len()), spelling.clone(), *name_len)
+        });
+        spellings.dedup_by(|(spelling, _, _), (other, _, _)| spelling == other);
+        spellings
+            .into_iter()
+            .map(|(spelling, phone, _)| (spelling, phone))
+            .collect()
+    }
+
+    // matches the longest spelling at each point, so "tʃ" is CH rather than T SH.
+    // if there are stress marks, vowels without one are unstressed.
+    // None if some of it isn't a known spelling
+    pub(super) fn phones_from_ipa(&self, ipa: &str) -> Option<Vec<(Phone, Option<u8>)>> {
+        let mut normal = String::new();
+        for c in ipa
+            .chars()
+            .filter(|c| !c.is_whitespace() && !IGNORED.contains(c))
+        {
+            match ALIASES.iter().find(|(alias, _)| *alias == c) {
+                Some((_, spelling)) => normal.push_str(spelling),
+                None => normal.push(c),
+            }
+        }
+        let marked = normal.contains(|c| STRESS_MARKS.iter().any(|(mark, _)| *mark == c));
+        let mut stress = None;
+        let mut rest = normal.as_str();
+        let mut phones = Vec::new();
+        while !rest.is_empty() {
+            if let Some((mark, level)) = STRESS_MARKS
+                .iter()
+                .find(|(mark, _)| rest.starts_with(*mark))
+            {
+                stress = Some(*level);
+                rest = &rest[mark.len_utf8()..];
+                continue;
+            }
+            let (spelling, phone) = self
+                .ipa_spellings
+                .iter()
+                .find(|(spelling, _)| rest.starts_with(spelling.as_str()))?;
+            let phone_stress = match phone {
+                Phone::Vowel(_) if marked => Some(stress.take().unwrap_or(0)),
+                _ => None,
+            };
+            phones.push((*phone, phone_stress));
+            rest = &rest[spelling.len()..];
+        }
+        Some(phones)
+    }
+
+    // writes a pronunciation in IPA, without stress
+    pub fn to_ipa(&self, pronounce: &Pronounce) -> String {
+        pronounce
+            .phones()
+            .filter_map(|phone| match phone {
+                Phone::Vowel(index) | Phone::Consonant(index) => {
+                    self.phones[index as usize].ipa.split_whitespace().next()
+                }
+                Phone::Silent => None,
+            })
+            .collect()
+    }
+}
@@ -1,27 +1,137 @@
-use csv::ReaderBuilder;
+use crate::LoadError;
+use csv::{ReaderBuilder, StringRecord};
+use serde_derive::Deserialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-static VOWELS: &str = include_str!("../../../preload/output/pronounce/vowels.csv");
-static CONSONTANTS: &str = include_str!("../../../preload/output/pronounce/consonants.csv");
+// a vowel or consonant and where it is in its chart, its index in a table is its id
+#[derive(Debug, Clone)]
+pub struct PhoneEntry {
+    pub name: Cow<'static, str>,
+    pub vowel: bool,
+    pub x: f32,
+    pub y: f32,
+    // only set for diphthongs, (x, y) is then the middle of the glide
+    pub glide: Option<Glide>,
+    // place, manner, voicing, rounding, nasality, length and offglide, each from 0 to 1
+    pub features: [f32; FEATURES],
+    // the ways it is written in IPA, separated by spaces, the usual one first
+    pub ipa: Cow<'static, str>,
+}
+
+pub const FEATURES: usize = 7;
+
+// where a diphthong (such as AY) starts and ends
+#[derive(Debug, Clone, Copy)]
+pub struct Glide {
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+}
+
+// the contents of the csv files in preload/output/pronounce
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PhoneTables {
+    pub vowels: String,
+    pub consonants: String,
+    pub features: String,
+    pub ipa: String,
+}
 
-type PhonemeMap = HashMap<String, (f32, f32)>;
-fn get_phoneme_map(csv_string: &str) -> PhonemeMap {
-    let mut reader = ReaderBuilder::new()
+// the tables in preload/output/pronounce, sorted by build.rs
+#[cfg(feature = "embedded")]
+include!(concat!(env!("OUT_DIR"), "/phones.rs"));
+
+// the field at `i` of a record
+fn field(record: &StringRecord, i: usize) -> Result<&str, LoadError> {
+    record
+        .get(i)
+        .ok_or_else(|| LoadError::invalid(format!("missing column {} in {:?}", i, record)))
+}
+fn number(record: &StringRecord, i: usize) -> Result<f32, LoadError> {
+    let field = field(record, i)?;
+    field
+        .parse()
+        .map_err(|_| LoadError::invalid(format!("{} is not a number", field)))
+}
+fn records(csv_string: &str) -> impl Iterator<Item = Result<StringRecord, LoadError>> + '_ {
+    ReaderBuilder::new()
         .has_headers(false)
-        .from_reader(csv_string.as_bytes());
-    reader
-        .records()
+        // diphthongs have more columns
+        .flexible(true)
+        .from_reader(csv_string.as_bytes())
+        .into_records()
+        .map(|record| Ok(record?))
+}
+
+fn get_phone_entries(
+    csv_string: &str,
+    vowel: bool,
+) -> impl Iterator<Item = Result<PhoneEntry, LoadError>> + '_ {
+    records(csv_string).map(move |record| {
+        let record = record?;
+        let glide = if record.len() >= 7 {
+            Some(Glide {
+                start: (number(&record, 3)?, number(&record, 4)?),
+                end: (number(&record, 5)?, number(&record, 6)?),
+            })
+        } else {
+            None
+        };
+        Ok(PhoneEntry {
+            name: Cow::Owned(field(&record, 0)?.to_owned()),
+            vowel,
+            x: number(&record, 1)?,
+            y: number(&record, 2)?,
+            glide,
+            features: [0.0; FEATURES],
+            ipa: Cow::Borrowed(""),
+        })
+    })
+}
+
+// the IPA spellings of each phone, by name
+fn get_ipa(csv_string: &str) -> Result<HashMap<String, String>, LoadError> {
+    records(csv_string)
+        .map(|record| {
+            let record = record?;
+            Ok((field(&record, 0)?.to_owned(), field(&record, 1)?.to_owned()))
+        })
+        .collect()
+}
+
+// the articulatory features of each phone, by name
+fn get_features(csv_string: &str) -> Result<HashMap<String, [f32; FEATURES]>, LoadError> {
+    records(csv_string)
         .map(|record| {
-            let record = record.unwrap();
-            let key = record.get(0).unwrap();
-            let x = record.get(1).unwrap().parse::<f32>().unwrap();
-            let y = record.get(2).unwrap().parse::<f32>().unwrap();
-            (key.to_owned(), (x, y))
+            let record = record?;
+            let mut features = [0.0; FEATURES];
+            for (i, feature) in features.iter_mut().enumerate() {
+                *feature = number(&record, i + 1)?;
+            }
+            Ok((field(&record, 0)?.to_owned(), features))
         })
         .collect()
 }
-pub fn read_pronounce() -> (PhonemeMap, PhonemeMap) {
-    let vowel_map = get_phoneme_map(VOWELS);
-    let consonant_map = get_phoneme_map(CONSONTANTS);
-    (vowel_map, consonant_map)
+#[cfg(feature = "embedded")]
+pub fn read_pronounce() -> Cow<'static, [PhoneEntry]> {
+    Cow::Borrowed(PHONES)
+}
+pub fn parse_pronounce(tables: &PhoneTables) -> Result<Cow<'static, [PhoneEntry]>, LoadError> {
+    let features = get_features(&tables.features)?;
+    let mut ipa = get_ipa(&tables.ipa)?;
+    let mut phones: Vec<PhoneEntry> = get_phone_entries(&tables.vowels, true)
+        .chain(get_phone_entries(&tables.consonants, false))
+        .map(|phone| {
+            let mut phone = phone?;
+            phone.features = *features.get(phone.name.as_ref()).ok_or_else(|| {
+                LoadError::invalid(format!("no articulatory features for {}", phone.name))
+            })?;
+            phone.ipa = Cow::Owned(ipa.remove(phone.name.as_ref()).ok_or_else(|| {
+                LoadError::invalid(format!("no IPA spelling for {}", phone.name))
+            })?);
+            Ok(phone)
+        })
+        .collect::<Result<_, LoadError>>()?;
+    phones.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Cow::Owned(phones))
 }
@@ -0,0 +1,69 @@
+use super::cmudict_data::Dict;
+use super::{Phone, PronounceBuilder};
+use crate::LoadError;
+
+// where the pronunciations of words come from, such as a dictionary of one language
+// or the spelling rules of another. the phones have to be from the builder's phone
+// table, so the builder is passed in to look them up by name
+pub trait PronunciationSource: Send + Sync {
+    // the phones of a word as it is written, or None if the source doesn't know it
+    fn phones(&self, word: &str, builder: &PronounceBuilder) -> Option<Vec<Phone>>;
+    // the phones with the stress of their vowels like cmudict's: 0 for none, 1 for primary
+    // and 2 for secondary. rhymes start at the last stressed vowel, so sources that know
+    // stress should give it here
+    fn stressed_phones(
+        &self,
+        word: &str,
+        builder: &PronounceBuilder,
+    ) -> Option<Vec<(Phone, Option<u8>)>> {
+        let phones = self.phones(word, builder)?;
+        Some(phones.into_iter().map(|phone| (phone, None)).collect())
+    }
+    // every word the source knows, to look for puns among. sources made of rules
+    // can't list their words, so this is empty unless a source overrides it
+    fn words(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+// a dictionary in the cmudict.bin format, with its phone indices already matched
+// to the builder's phones
+pub(super) struct DictSource {
+    dict: Dict,
+    phones: Vec<Phone>,
+}
+impl DictSource {
+    pub(super) fn new(dict: Dict, builder: &PronounceBuilder) -> Result<DictSource, LoadError> {
+        let phones = dict
+            .phone_names()
+            .iter()
+            .map(|name| {
+                builder
+                    .phone_from_arpabet(name)
+                    .ok_or_else(|| LoadError::invalid(format!("no phone table entry for {}", name)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(DictSource { dict, phones })
+    }
+}
+impl PronunciationSource for DictSource {
+    fn phones(&self, word: &str, builder: &PronounceBuilder) -> Option<Vec<Phone>> {
+        let phones = self.stressed_phones(word, builder)?;
+        Some(phones.into_iter().map(|(phone, _)| phone).collect())
+    }
+    fn stressed_phones(
+        &self,
+        word: &str,
+        _: &PronounceBuilder,
+    ) -> Option<Vec<(Phone, Option<u8>)>> {
+        let phones = self.dict.get(&word.to_ascii_uppercase())?;
+        Some(
+            phones
+                .map(|(index, stress)| (self.phones[index], stress))
+                .collect(),
+        )
+    }
+    fn words(&self) -> Vec<String> {
+        self.dict.words()
+    }
+}
@@ -0,0 +1,130 @@
+use super::source::PronunciationSource;
+use super::{Phone, PronounceBuilder};
+
+// Spanish is nearly written as it is said, so rules for each letter are enough.
+// it uses the closest English phones, and seseo (z and soft c said like s)
+pub struct SpanishSource;
+
+fn is_vowel(c: char) -> bool {
+    matches!(
+        c,
+        'a' | 'e' | 'i' | 'o' | 'u' | 'á' | 'é' | 'í' | 'ó' | 'ú' | 'ü'
+    )
+}
+// e and i soften c and g before them
+fn is_front(c: Option<char>) -> bool {
+    matches!(c, Some('e' | 'i' | 'é' | 'í'))
+}
+
+fn is_accented(c: char) -> bool {
+    matches!(c, 'á' | 'é' | 'í' | 'ó' | 'ú')
+}
+const VOWEL_PHONES: &[&str] = &["AA", "EH", "IY", "OW", "UW", "AY", "EY", "OY", "AW"];
+
+// the arpabet phones of a word with the stress of each vowel, and None for anything
+// that isn't Spanish spelling
+fn spanish_arpabet(word: &str) -> Option<Vec<(&'static str, Option<u8>)>> {
+    let mut chars: Vec<char> = word.to_lowercase().chars().collect();
+    // a y after a vowel at the end is a vowel, such as "hoy"
+    if chars.len() > 1 && chars.ends_with(&['y']) && is_vowel(chars[chars.len() - 2]) {
+        *chars.last_mut()? = 'i';
+    }
+    let mut arpabet: Vec<&str> = Vec::with_capacity(chars.len());
+    // the vowel written with an accent, which is the stressed one
+    let mut accented = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        let (phones, len): (&[&str], usize) = match (chars[i], next) {
+            // diphthongs are one syllable
+            ('a' | 'á', Some('i')) => (&["AY"], 2),
+            ('e' | 'é', Some('i')) => (&["EY"], 2),
+            ('o' | 'ó', Some('i')) => (&["OY"], 2),
+            ('a' | 'á', Some('u')) => (&["AW"], 2),
+            // unstressed i and u next to another vowel glide into it, such as "bien"
+            ('i', _) if next.is_some_and(is_vowel) => (&["Y"], 1),
+            ('u', _) if next.is_some_and(is_vowel) => (&["W"], 1),
+            ('a' | 'á', _) => (&["AA"], 1),
+            ('e' | 'é', _) => (&["EH"], 1),
+            ('i' | 'í', _) => (&["IY"], 1),
+            ('o' | 'ó', _) => (&["OW"], 1),
+            ('u' | 'ú', _) => (&["UW"], 1),
+            ('ü', _) => (&["W"], 1),
+            ('y', None) => (&["IY"], 1),
+            ('y', _) => (&["Y"], 1),
+            ('c', Some('h')) => (&["CH"], 2),
+            ('c', _) if is_front(next) => (&["S"], 1),
+            ('q', Some('u')) => (&["K"], 2),
+            ('c' | 'k' | 'q', _) => (&["K"], 1),
+            // the u of gue and gui is silent
+            ('g', Some('u')) if is_front(chars.get(i + 2).copied()) => (&["G"], 2),
+            ('g', _) if is_front(next) => (&["HH"], 1),
+            ('g', _) => (&["G"], 1),
+            ('j', _) => (&["HH"], 1),
+            ('h', _) => (&[], 1),
+            ('l', Some('l')) => (&["Y"], 2),
+            ('r', Some('r')) => (&["R"], 2),
+            ('ñ', _) => (&["N", "Y"], 1),
+            ('x', _) => (&["K", "S"], 1),
+            ('b' | 'v', _) => (&["B"], 1),
+            ('s' | 'z', _) => (&["S"], 1),
+            ('d', _) => (&["D"], 1),
+            ('f', _) => (&["F"], 1),
+            ('l', _) => (&["L"], 1),
+            ('m', _) => (&["M"], 1),
+            ('n', _) => (&["N"], 1),
+            ('p', _) => (&["P"], 1),
+            ('r', _) => (&["R"], 1),
+            ('t', _) => (&["T"], 1),
+            ('w', _) => (&["W"], 1),
+            _ => return None,
+        };
+        if chars[i..i + len].iter().any(|c| is_accented(*c)) {
+            accented = Some(arpabet.len());
+        }
+        arpabet.extend(phones);
+        i += len;
+    }
+    // without an accent, words ending in a vowel, n or s are stressed on the second
+    // to last syllable and the rest on the last
+    let vowels: Vec<usize> = (0..arpabet.len())
+        .filter(|i| VOWEL_PHONES.contains(&arpabet[*i]))
+        .collect();
+    let stressed = accented.or_else(|| {
+        let penultimate = matches!(chars.last(), Some(c) if is_vowel(*c) || *c == 'n' || *c == 's');
+        let from_end = if penultimate { 2 } else { 1 };
+        vowels
+            .len()
+            .checked_sub(from_end)
+            .map(|vowel| vowels[vowel])
+    });
+    Some(
+        arpabet
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let stress = vowels
+                    .contains(&i)
+                    .then_some(if Some(i) == stressed { 1 } else { 0 });
+                (*name, stress)
+            })
+            .collect(),
+    )
+}
+
+impl PronunciationSource for SpanishSource {
+    fn phones(&self, word: &str, builder: &PronounceBuilder) -> Option<Vec<Phone>> {
+        let phones = self.stressed_phones(word, builder)?;
+        Some(phones.into_iter().map(|(phone, _)| phone).collect())
+    }
+    fn stressed_phones(
+        &self,
+        word: &str,
+        builder: &PronounceBuilder,
+    ) -> Option<Vec<(Phone, Option<u8>)>> {
+        spanish_arpabet(word)?
+            .into_iter()
+            .map(|(name, stress)| Some((builder.phone_from_arpabet(name)?, stress)))
+            .collect()
+    }
+}
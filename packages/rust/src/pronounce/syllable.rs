@@ -0,0 +1,116 @@
+use super::{Phone, Pronounce, PronounceBuilder};
+
+// the clusters of more than one consonant an English syllable can start with
+const ONSETS: &[&[&str]] = &[
+    &["P", "R"],
+    &["P", "L"],
+    &["P", "Y"],
+    &["B", "R"],
+    &["B", "L"],
+    &["B", "Y"],
+    &["T", "R"],
+    &["T", "W"],
+    &["D", "R"],
+    &["D", "W"],
+    &["K", "R"],
+    &["K", "L"],
+    &["K", "W"],
+    &["K", "Y"],
+    &["G", "R"],
+    &["G", "L"],
+    &["G", "W"],
+    &["G", "Y"],
+    &["F", "R"],
+    &["F", "L"],
+    &["F", "Y"],
+    &["V", "Y"],
+    &["TH", "R"],
+    &["TH", "W"],
+    &["SH", "R"],
+    &["HH", "Y"],
+    &["M", "Y"],
+    &["S", "P"],
+    &["S", "T"],
+    &["S", "K"],
+    &["S", "M"],
+    &["S", "N"],
+    &["S", "L"],
+    &["S", "W"],
+    &["S", "F"],
+    &["S", "P", "R"],
+    &["S", "P", "L"],
+    &["S", "P", "Y"],
+    &["S", "T", "R"],
+    &["S", "K", "R"],
+    &["S", "K", "L"],
+    &["S", "K", "W"],
+    &["S", "K", "Y"],
+];
+
+// a syllable: the consonants before its vowel, the vowel, and the consonants after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syllable<'a> {
+    pub onset: &'a [Phone],
+    pub nucleus: Phone,
+    pub coda: &'a [Phone],
+    // 0 for none, 1 for primary and 2 for secondary, if known
+    pub stress: Option<u8>,
+}
+
+impl Pronounce {
+    pub fn syllables(&self) -> Vec<Syllable<'_>> {
+        // the normalized phones alternate consonant and vowel groups, starting and
+        // ending with consonants, and every vowel group is one vowel
+        let groups: Vec<&[Phone]> = self
+            .get_grouped()
+            .map(|group| if group == [Phone::Silent] { &[] } else { group })
+            .collect();
+        (0..self.onsets.len())
+            .map(|i| {
+                let before = groups[i * 2];
+                let after = groups[i * 2 + 2];
+                let next_onset = self.onsets.get(i + 1).copied().unwrap_or(0) as usize;
+                Syllable {
+                    onset: &before[before.len() - self.onsets[i] as usize..],
+                    nucleus: groups[i * 2 + 1][0],
+                    coda: &after[..after.len() - next_onset],
+                    stress: self.stress[i],
+                }
+            })
+            .collect()
+    }
+}
+
+impl PronounceBuilder {
+    // the most consonants from the end of a cluster that can start an English syllable
+    fn onset_len(&self, cluster: &[Phone]) -> usize {
+        let names: Vec<&str> = cluster
+            .iter()
+            .map(|phone| match phone {
+                Phone::Vowel(index) | Phone::Consonant(index) => {
+                    self.phones[*index as usize].name.as_ref()
+                }
+                Phone::Silent => "",
+            })
+            .collect();
+        (1..=names.len())
+            .rev()
+            .find(|len| {
+                let onset = &names[names.len() - len..];
+                match onset {
+                    [single] => *single != "NG",
+                    _ => ONSETS.contains(&onset),
+                }
+            })
+            .unwrap_or(0)
+    }
+    // makes a pronunciation with its syllables split by the maximal onset principle:
+    // consonants between vowels start the next syllable if English allows it
+    pub(super) fn syllabify(&self, phones: Vec<(Phone, Option<u8>)>) -> Pronounce {
+        Pronounce::syllabified(phones, |cluster| self.onset_len(cluster))
+    }
+    // like `Pronounce::new`, but split by the same rule as the builder's other words
+    pub fn from_phones(&self, phones: Vec<Phone>) -> Pronounce {
+        self.syllabify(phones.into_iter().map(|phone| (phone, None)).collect())
+    }
+}
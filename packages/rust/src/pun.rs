@@ -0,0 +1,94 @@
+use crate::pronounce::{PhoneDistances, Pronounce, PronounceBuilder};
+use crate::LoadError;
+use csv::ReaderBuilder;
+use std::collections::HashMap;
+
+#[cfg(feature = "embedded")]
+static PHRASES: &str = include_str!("../../preload/output/phrases.csv");
+
+#[cfg(feature = "embedded")]
+pub fn read_phrases() -> Vec<(String, String)> {
+    parse_phrases(PHRASES).expect("the embedded phrases are valid")
+}
+
+// parses the phrases.csv written by preload: phrase and arpabet
+pub fn parse_phrases(csv_string: &str) -> Result<Vec<(String, String)>, LoadError> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(csv_string.as_bytes());
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            match (record.get(0), record.get(1)) {
+                (Some(phrase), Some(pronounce)) => Ok((phrase.to_owned(), pronounce.to_owned())),
+                _ => Err(LoadError::invalid(format!(
+                    "expected phrase,arpabet in {:?}",
+                    record
+                ))),
+            }
+        })
+        .collect()
+}
+
+// replacing the other way around: finds the words and phrases that sound like a
+// framework, for release names and pun headlines
+pub struct PunFinder {
+    // by syllable count, because only pronunciations with as many syllables are close
+    index: HashMap<u32, Vec<(String, Pronounce)>>,
+}
+impl PunFinder {
+    // indexes the phrases, as (phrase, arpabet), and every word the builder can list
+    pub fn new(builder: &PronounceBuilder, phrases: Vec<(String, String)>) -> PunFinder {
+        let words = builder.words().into_iter().map(|word| {
+            let pronounce = builder.pronounce(&word);
+            (word.to_ascii_lowercase(), pronounce)
+        });
+        let phrases = phrases.into_iter().filter_map(|(phrase, arpabet)| {
+            let pronounce = builder.pronounce_from_arpabet(&arpabet)?;
+            Some((phrase, pronounce))
+        });
+        let mut index: HashMap<u32, Vec<(String, Pronounce)>> = HashMap::new();
+        for (text, pronounce) in words.chain(phrases) {
+            index
+                .entry(pronounce.syllable_count())
+                .or_default()
+                .push((text, pronounce));
+        }
+        PunFinder { index }
+    }
+
+    // at most limit words and phrases closer than threshold to the pronunciation,
+    // closest first
+    pub fn find(
+        &self,
+        pronounce: &Pronounce,
+        distances: &PhoneDistances,
+        threshold: f32,
+        limit: usize,
+    ) -> Vec<(f32, &str)> {
+        self.find_where(pronounce, distances, threshold, limit, |_| true)
+    }
+    // like `find`, but only counts the words and phrases keep is true for
+    pub fn find_where(
+        &self,
+        pronounce: &Pronounce,
+        distances: &PhoneDistances,
+        threshold: f32,
+        limit: usize,
+        keep: impl Fn(&str) -> bool,
+    ) -> Vec<(f32, &str)> {
+        let Some(candidates) = self.index.get(&pronounce.syllable_count()) else {
+            return Vec::new();
+        };
+        let mut puns: Vec<(f32, &str)> = candidates
+            .iter()
+            .filter(|(text, _)| keep(text))
+            .map(|(text, other)| (other.distance(pronounce, distances), text.as_str()))
+            .filter(|(distance, _)| *distance < threshold)
+            .collect();
+        puns.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(b.1)));
+        puns.truncate(limit);
+        puns
+    }
+}
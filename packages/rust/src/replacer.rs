@@ -1,60 +1,495 @@
+use crate::pronounce::DistanceConfig;
+use crate::pronounce::PhoneDistances;
 use crate::pronounce::Pronounce;
 use crate::pronounce::PronounceBuilder;
+use crate::pronounce::PronunciationSource;
+use crate::pun::PunFinder;
+use crate::LoadError;
 use itertools::Itertools;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
 
+mod pos;
+pub use pos::{PartOfSpeech, Tagger};
+mod protect;
+pub use protect::{fold_case, is_stopword, Protected, STOPWORDS};
+mod stream;
+pub use stream::{Edit, EditOutOfRange, ReplaceStream, Splice};
+#[cfg(feature = "rayon")]
+mod parallel;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Replacement {
-    Replace(String),
+    // the framework, and details about the word it replaced
+    Replace(String, Replaced),
     Keep(String),
 }
 
-pub fn replace_words(s: &str, replace_fn: impl Fn(&str) -> Option<String>) -> Vec<Replacement> {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replaced {
+    pub original: String,
+    pub part_of_speech: PartOfSpeech,
+}
+
+impl Replacement {
+    // the text this replacement was made from
+    pub fn original(&self) -> &str {
+        match self {
+            Replacement::Replace(_, replaced) => &replaced.original,
+            Replacement::Keep(part) => part,
+        }
+    }
+}
+
+// punctuation and whitespace split words, except apostrophes
+fn is_word_char(c: char) -> bool {
+    c == '\'' || !(c.is_ascii_punctuation() || c.is_whitespace())
+}
+
+// splits s into alternating words and the parts between them
+fn split_words(s: &str) -> Vec<(bool, String)> {
     s.chars()
-        // remove punctuation, except apostrophes
-        .group_by(|c| *c == '\'' || !(c.is_ascii_punctuation() || c.is_whitespace()))
+        .group_by(|c| is_word_char(*c))
         .into_iter()
-        .map(|(is_word, group)| {
-            let part = group.collect::<String>();
-            match (is_word, part.is_ascii()) {
-                (true, true) => replace_fn(&part)
-                    .map(Replacement::Replace)
-                    .unwrap_or(Replacement::Keep(part)),
-                _ => Replacement::Keep(part),
-            }
+        .map(|(is_word, group)| (is_word, group.collect()))
+        .collect()
+}
+
+// tags the part if it is a word that could be replaced
+fn tag_part(tagger: &mut Tagger, is_word: bool, part: &str) -> Option<PartOfSpeech> {
+    is_word.then(|| tagger.tag(part))
+}
+
+fn replace_part(
+    is_word: bool,
+    part: String,
+    tagger: &mut Tagger,
+    is_protected: &impl Fn(&str) -> bool,
+    replace_fn: &mut impl FnMut(&str, PartOfSpeech) -> Option<String>,
+) -> Replacement {
+    // protected words still get tagged, they are context for the next word
+    let part_of_speech = match tag_part(tagger, is_word, &part) {
+        Some(part_of_speech) => part_of_speech,
+        None => return Replacement::Keep(part),
+    };
+    if is_protected(&part) {
+        return Replacement::Keep(part);
+    }
+    match replace_fn(&part, part_of_speech) {
+        Some(framework) => Replacement::Replace(
+            framework,
+            Replaced {
+                original: part,
+                part_of_speech,
+            },
+        ),
+        None => Replacement::Keep(part),
+    }
+}
+
+// splits s into words and the parts between them, replacing words that aren't protected
+pub fn replace_words(
+    s: &str,
+    is_protected: impl Fn(&str) -> bool,
+    mut replace_fn: impl FnMut(&str, PartOfSpeech) -> Option<String>,
+) -> Vec<Replacement> {
+    let mut tagger = Tagger::new();
+    split_words(s)
+        .into_iter()
+        .map(|(is_word, part)| {
+            replace_part(is_word, part, &mut tagger, &is_protected, &mut replace_fn)
         })
         .collect()
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReplaceOptions {
+    pub mode: ReplaceMode,
+    // candidates at least this far away are never picked
+    pub threshold: f32,
+    // frameworks with fewer stars are never picked. 0 by default, so popularity only
+    // ranks the candidates through `star_weight`
+    pub star_threshold: u32,
+    // how much popularity lowers the score of a candidate. the most starred word gets
+    // all of it and the others get less by their log stars, so keep it well under the
+    // threshold to only break close ties (0 turns it off)
+    pub star_weight: f32,
+    // when set, picks randomly among the candidates instead of always picking the closest
+    pub sampling: Option<Sampling>,
+    // when set, frameworks that were already used are less likely to be picked again
+    pub variety: Option<Variety>,
+    // skips the built in `STOPWORDS` as well as the replacer's own stopwords
+    pub stopwords: bool,
+    pub protected: Protected,
+    // only words guessed to be one of these are replaced, or any word if not set
+    pub parts_of_speech: Option<Vec<PartOfSpeech>>,
+}
+impl Default for ReplaceOptions {
+    fn default() -> Self {
+        ReplaceOptions {
+            mode: ReplaceMode::default(),
+            threshold: 0.2,
+            star_threshold: 0,
+            star_weight: 0.05,
+            sampling: None,
+            variety: None,
+            stopwords: false,
+            protected: Protected::default(),
+            parts_of_speech: None,
+        }
+    }
+}
+
+// how a framework has to sound like a word to replace it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplaceMode {
+    // like the whole word
+    #[default]
+    Sound,
+    // like the word from its last stressed vowel on, such as "tact" and React
+    Rhyme,
+    // like the start of the word up to its first vowel, such as "angry" and Angular
+    Alliteration,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Sampling {
+    // higher temperatures make worse candidates more likely, 0 always picks the best
+    pub temperature: f32,
+    // the same seed and input always give the same output
+    pub seed: u64,
+}
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling {
+            temperature: 0.1,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Variety {
+    // added to the score of a framework for every time it was already used
+    pub penalty: f32,
+    // how many words back a use still counts, or the whole document if not set
+    pub window: Option<usize>,
+}
+impl Default for Variety {
+    fn default() -> Self {
+        Variety {
+            penalty: 0.1,
+            window: None,
+        }
+    }
+}
+
+// picks an index with probability softmax(-score / temperature)
+fn sample(scores: &[f32], temperature: f32, rng: &mut SmallRng) -> Option<usize> {
+    let min = scores.iter().cloned().reduce(f32::min)?;
+    if temperature <= 0.0 {
+        return scores.iter().position(|score| *score == min);
+    }
+    // subtract the best score so the best weight is 1 and nothing overflows
+    let weights: Vec<f32> = scores
+        .iter()
+        .map(|score| (-(score - min) / temperature).exp())
+        .collect();
+    let mut target = rng.gen::<f32>() * weights.iter().sum::<f32>();
+    for (i, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return Some(i);
+        }
+        target -= weight;
+    }
+    // floating point error can leave a tiny bit of target, so use the last one
+    Some(weights.len() - 1)
+}
+
 pub struct Replacer {
     words: Vec<(String, Pronounce, u64)>,
     builder: PronounceBuilder,
+    distances: PhoneDistances,
+    // log stars of the most starred word, to scale the star bonus by
+    max_log_stars: f32,
+    // lowercase words that are never replaced when stopwords are on
+    stopwords: HashSet<String>,
+    // counts the changes that could change replacements, see `version`
+    version: u64,
 }
 impl Replacer {
+    #[cfg(feature = "embedded")]
     pub fn new(words: Vec<(String, String, u64)>) -> Self {
-        let builder = PronounceBuilder::new();
+        Self::with_config(words, DistanceConfig::default())
+    }
+    #[cfg(feature = "embedded")]
+    pub fn with_config(words: Vec<(String, String, u64)>, config: DistanceConfig) -> Self {
+        Self::with_builder(words, PronounceBuilder::new(), config)
+    }
+    pub fn with_builder(
+        words: Vec<(String, String, u64)>,
+        builder: PronounceBuilder,
+        config: DistanceConfig,
+    ) -> Self {
+        // words with phones the builder doesn't know can't be compared, so are left out
         let words = words
             .into_iter()
-            .map(|(word, arpabet, stars)| {
-                let pronounce = builder.pronounce_from_arpabet(&arpabet);
-                (word, pronounce, stars)
+            .filter_map(|(word, arpabet, stars)| {
+                let pronounce = builder.pronounce_from_arpabet(&arpabet)?;
+                Some((word, pronounce, stars))
             })
+            .collect::<Vec<_>>();
+        let max_stars = words.iter().map(|word| word.2).max().unwrap_or(0);
+        Replacer {
+            words,
+            max_log_stars: (max_stars as f32).ln_1p(),
+            distances: builder.distances(&config),
+            builder,
+            stopwords: HashSet::new(),
+            version: 0,
+        }
+    }
+
+    // changes whenever the replacer does, so replacements kept from before can be
+    // made again
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn set_config(&mut self, config: DistanceConfig) {
+        self.distances = self.builder.distances(&config);
+        self.version += 1;
+    }
+
+    pub fn builder(&self) -> &PronounceBuilder {
+        &self.builder
+    }
+
+    // see `PronounceBuilder::add_dict`
+    pub fn add_dict(&mut self, dict: Vec<u8>) -> Result<(), LoadError> {
+        self.builder.add_dict(dict)?;
+        self.version += 1;
+        Ok(())
+    }
+    // see `PronounceBuilder::add_source`
+    pub fn add_source(&mut self, source: impl PronunciationSource + 'static) {
+        self.builder.add_source(source);
+        self.version += 1;
+    }
+    // see `PronounceBuilder::add_fallback_source`
+    pub fn add_fallback_source(&mut self, source: impl PronunciationSource + 'static) {
+        self.builder.add_fallback_source(source);
+        self.version += 1;
+    }
+
+    pub fn add_stopwords<I>(&mut self, words: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.stopwords
+            .extend(words.into_iter().map(|word| fold_case(word.as_ref())));
+        self.version += 1;
+    }
+
+    // the words and phrases that sound like a framework, or like any other word
+    // if it isn't one of the frameworks, leaving out the framework's own name
+    pub fn puns<'a>(
+        &self,
+        finder: &'a PunFinder,
+        framework: &str,
+        threshold: f32,
+        limit: usize,
+    ) -> Vec<(f32, &'a str)> {
+        let pronounce = match self
+            .words
+            .iter()
+            .find(|(word, _, _)| word.eq_ignore_ascii_case(framework))
+        {
+            Some((_, pronounce, _)) => pronounce.clone(),
+            None => self.builder.pronounce(framework),
+        };
+        let name: String = framework
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
             .collect();
-        Replacer { words, builder }
-    }
-
-    pub fn replace(&self, s: &str, threshold: f32, star_threshold: u32) -> Vec<Replacement> {
-        replace_words(s, |word| {
-            let pronounce = self.builder.pronounce(word);
-            self.words
-                .iter()
-                // filter out words that don't have enough stars
-                .filter(|other| other.2 >= star_threshold as u64)
-                // map to (difference, word)
-                .map(|other| (other.1.distance(&pronounce), other.0.clone()))
-                // filter out words that are too different
-                .filter(|a| a.0 < threshold)
-                // find the smallest difference
-                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
-                .map(|a| a.1)
+        finder.find_where(&pronounce, &self.distances, threshold, limit, |text| {
+            let text: String = text.chars().filter(char::is_ascii_alphanumeric).collect();
+            !text.eq_ignore_ascii_case(&name)
         })
     }
+
+    fn is_protected(&self, word: &str, options: &ReplaceOptions) -> bool {
+        options.protected.contains(word)
+            || (options.stopwords
+                && (is_stopword(word) || self.stopwords.contains(&fold_case(word))))
+    }
+
+    pub fn replace(
+        &self,
+        s: &str,
+        threshold: f32,
+        star_threshold: u32,
+        mode: ReplaceMode,
+    ) -> Vec<Replacement> {
+        self.replace_with(
+            s,
+            &ReplaceOptions {
+                mode,
+                threshold,
+                star_threshold,
+                ..ReplaceOptions::default()
+            },
+        )
+    }
+
+    // lower is better: the phonetic distance minus a bonus for being popular, which is
+    // never more than `star_weight`
+    fn score(&self, distance: f32, stars: u64, options: &ReplaceOptions) -> f32 {
+        if self.max_log_stars == 0.0 {
+            return distance;
+        }
+        distance - options.star_weight * (stars as f32).ln_1p() / self.max_log_stars
+    }
+
+    // finds (score, word) of every word close enough to the pronunciation
+    fn candidates<'a: 'p, 'p>(
+        &'a self,
+        pronounce: &'p Pronounce,
+        options: &'a ReplaceOptions,
+    ) -> impl Iterator<Item = (f32, &'a str)> + 'p {
+        self.words
+            .iter()
+            // filter out words that don't have enough stars
+            .filter(move |other| other.2 >= options.star_threshold as u64)
+            // map to (difference, word, stars)
+            .map(move |other| {
+                let distance = match options.mode {
+                    ReplaceMode::Sound => other.1.distance(pronounce, &self.distances),
+                    ReplaceMode::Rhyme => other.1.rhyme_distance(pronounce, &self.distances),
+                    ReplaceMode::Alliteration => {
+                        other.1.alliteration_distance(pronounce, &self.distances)
+                    }
+                };
+                (distance, other.0.as_str(), other.2)
+            })
+            // filter out words that are too different, popularity can't make up for that
+            .filter(move |a| a.0 < options.threshold)
+            .map(move |(distance, word, stars)| (self.score(distance, stars, options), word))
+    }
+
+    // the candidates of a word, before variety
+    fn scored<'a>(&'a self, word: &str, options: &'a ReplaceOptions) -> Vec<(f32, &'a str)> {
+        let pronounce = self.builder.pronounce(word);
+        self.candidates(&pronounce, options).collect()
+    }
+
+    pub fn replace_with(&self, s: &str, options: &ReplaceOptions) -> Vec<Replacement> {
+        let mut state = ReplaceState::new(self, options);
+        replace_words(
+            s,
+            |word| self.is_protected(word, options),
+            |word, part_of_speech| state.replace_word(word, part_of_speech),
+        )
+    }
+}
+
+// how many words `ReplaceState` keeps the candidates of, before forgetting them all so
+// a long stream of text doesn't grow it forever
+const MAX_SCORED: usize = 4096;
+
+// everything carried from one word to the next during a replacement
+struct ReplaceState<'a> {
+    replacer: &'a Replacer,
+    options: &'a ReplaceOptions,
+    rng: Option<SmallRng>,
+    // how many words have been seen so far
+    word_index: usize,
+    // (word index, framework) of earlier replacements that still count for variety
+    used: VecDeque<(usize, &'a str)>,
+    // the candidates of each uppercase word seen so far, so repeated words are scored once
+    scored: HashMap<String, Vec<(f32, &'a str)>>,
+}
+impl<'a> ReplaceState<'a> {
+    fn new(replacer: &'a Replacer, options: &'a ReplaceOptions) -> Self {
+        ReplaceState {
+            replacer,
+            options,
+            rng: options
+                .sampling
+                .as_ref()
+                .map(|sampling| SmallRng::seed_from_u64(sampling.seed)),
+            word_index: 0,
+            used: VecDeque::new(),
+            scored: HashMap::new(),
+        }
+    }
+
+    fn variety_penalty(&self, framework: &str) -> f32 {
+        match &self.options.variety {
+            Some(variety) => {
+                let uses = self.used.iter().filter(|(_, used)| *used == framework);
+                uses.count() as f32 * variety.penalty
+            }
+            None => 0.0,
+        }
+    }
+
+    fn pick(&mut self, word: &str) -> Option<&'a str> {
+        let word = word.to_uppercase();
+        if !self.scored.contains_key(&word) {
+            if self.scored.len() >= MAX_SCORED {
+                self.scored.clear();
+            }
+            let scored = self.replacer.scored(&word, self.options);
+            self.scored.insert(word.clone(), scored);
+        }
+        let candidates: Vec<(f32, &'a str)> = self.scored[&word]
+            .iter()
+            .map(|(score, framework)| (score + self.variety_penalty(framework), *framework))
+            .collect();
+        match (&self.options.sampling, &mut self.rng) {
+            (Some(sampling), Some(rng)) => {
+                let scores: Vec<f32> = candidates.iter().map(|a| a.0).collect();
+                sample(&scores, sampling.temperature, rng).map(|i| candidates[i].1)
+            }
+            // find the smallest score
+            _ => candidates
+                .into_iter()
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+                .map(|a| a.1),
+        }
+    }
+
+    fn replace_word(&mut self, word: &str, part_of_speech: PartOfSpeech) -> Option<String> {
+        let eligible = match &self.options.parts_of_speech {
+            Some(allowed) => allowed.contains(&part_of_speech),
+            None => true,
+        };
+        let picked = if eligible { self.pick(word) } else { None };
+        if let Some(variety) = &self.options.variety {
+            if let Some(framework) = picked {
+                self.used.push_back((self.word_index, framework));
+            }
+            // forget uses that fell out of the window
+            if let Some(window) = variety.window {
+                while let Some((index, _)) = self.used.front() {
+                    if index + window > self.word_index {
+                        break;
+                    }
+                    self.used.pop_front();
+                }
+            }
+        }
+        self.word_index += 1;
+        picked.map(|framework| framework.to_owned())
+    }
 }
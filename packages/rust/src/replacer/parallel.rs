@@ -0,0 +1,28 @@
+use super::{replace_words, split_words, ReplaceOptions, ReplaceState, Replacement, Replacer};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+impl Replacer {
+    // like `replace_with`, but scores the distinct words of s on every core first.
+    // picking still goes word by word, so sampling and variety give the same result
+    pub fn par_replace_with(&self, s: &str, options: &ReplaceOptions) -> Vec<Replacement> {
+        let words: HashSet<String> = split_words(s)
+            .into_iter()
+            .filter(|(is_word, part)| *is_word && !self.is_protected(part, options))
+            .map(|(_, word)| word.to_uppercase())
+            .collect();
+        let mut state = ReplaceState::new(self, options);
+        state.scored = words
+            .into_par_iter()
+            .map(|word| {
+                let scored = self.scored(&word, options);
+                (word, scored)
+            })
+            .collect();
+        replace_words(
+            s,
+            |word| self.is_protected(word, options),
+            |word, part_of_speech| state.replace_word(word, part_of_speech),
+        )
+    }
+}
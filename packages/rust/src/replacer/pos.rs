@@ -0,0 +1,144 @@
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PartOfSpeech {
+    Noun,
+    Verb,
+    Adjective,
+    Adverb,
+    Determiner,
+    Pronoun,
+    Preposition,
+    Conjunction,
+    // helping verbs like "is" and "would", which are a closed class unlike other verbs
+    Auxiliary,
+}
+
+static DETERMINERS: &[&str] = &[
+    "a", "all", "an", "any", "both", "each", "either", "every", "her", "his", "its", "my",
+    "neither", "no", "our", "some", "that", "the", "their", "these", "this", "those", "your",
+];
+static PRONOUNS: &[&str] = &[
+    "he",
+    "herself",
+    "him",
+    "himself",
+    "i",
+    "it",
+    "itself",
+    "me",
+    "mine",
+    "myself",
+    "ours",
+    "ourselves",
+    "she",
+    "theirs",
+    "them",
+    "themselves",
+    "they",
+    "us",
+    "we",
+    "what",
+    "which",
+    "who",
+    "whom",
+    "whose",
+    "you",
+    "yours",
+    "yourself",
+];
+static PREPOSITIONS: &[&str] = &[
+    "about", "above", "across", "after", "against", "along", "among", "around", "at", "before",
+    "behind", "below", "beneath", "beside", "between", "beyond", "by", "down", "during", "for",
+    "from", "in", "inside", "into", "near", "of", "off", "on", "onto", "out", "over", "past",
+    "since", "through", "to", "toward", "under", "until", "up", "upon", "with", "within",
+    "without",
+];
+static CONJUNCTIONS: &[&str] = &[
+    "although", "and", "because", "but", "if", "nor", "or", "so", "than", "though", "unless",
+    "when", "whether", "while", "yet",
+];
+static AUXILIARIES: &[&str] = &[
+    "am", "are", "be", "been", "being", "can", "could", "did", "do", "does", "had", "has", "have",
+    "is", "may", "might", "must", "shall", "should", "was", "were", "will", "would",
+];
+
+// checked in order, so longer suffixes that overlap come first
+static SUFFIXES: &[(&str, PartOfSpeech)] = &[
+    ("ness", PartOfSpeech::Noun),
+    ("tion", PartOfSpeech::Noun),
+    ("sion", PartOfSpeech::Noun),
+    ("ment", PartOfSpeech::Noun),
+    ("ship", PartOfSpeech::Noun),
+    ("hood", PartOfSpeech::Noun),
+    ("ity", PartOfSpeech::Noun),
+    ("ism", PartOfSpeech::Noun),
+    ("ist", PartOfSpeech::Noun),
+    ("ly", PartOfSpeech::Adverb),
+    ("able", PartOfSpeech::Adjective),
+    ("ible", PartOfSpeech::Adjective),
+    ("ous", PartOfSpeech::Adjective),
+    ("ful", PartOfSpeech::Adjective),
+    ("less", PartOfSpeech::Adjective),
+    ("ive", PartOfSpeech::Adjective),
+    ("ical", PartOfSpeech::Adjective),
+    ("ic", PartOfSpeech::Adjective),
+    ("ish", PartOfSpeech::Adjective),
+    ("ize", PartOfSpeech::Verb),
+    ("ise", PartOfSpeech::Verb),
+    ("ify", PartOfSpeech::Verb),
+    ("ate", PartOfSpeech::Verb),
+    ("ing", PartOfSpeech::Verb),
+    ("ed", PartOfSpeech::Verb),
+];
+
+fn closed_class(word: &str) -> Option<PartOfSpeech> {
+    // some words are in several lists, like "that", so the order matters
+    [
+        (DETERMINERS, PartOfSpeech::Determiner),
+        (PRONOUNS, PartOfSpeech::Pronoun),
+        (AUXILIARIES, PartOfSpeech::Auxiliary),
+        (CONJUNCTIONS, PartOfSpeech::Conjunction),
+        (PREPOSITIONS, PartOfSpeech::Preposition),
+    ]
+    .iter()
+    .find(|(list, _)| list.contains(&word))
+    .map(|(_, pos)| *pos)
+}
+
+fn suffix_class(word: &str) -> Option<PartOfSpeech> {
+    SUFFIXES
+        .iter()
+        // short words like "bed" or "only" are too short for suffixes to mean much
+        .find(|(suffix, _)| word.len() > suffix.len() + 2 && word.ends_with(suffix))
+        .map(|(_, pos)| *pos)
+}
+
+// guesses the part of speech of each word from the word itself and the one before it.
+// it never looks ahead, so adjectives without a suffix like "quick" come out as nouns
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tagger {
+    previous: Option<PartOfSpeech>,
+    previous_to: bool,
+}
+impl Tagger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn tag(&mut self, word: &str) -> PartOfSpeech {
+        let word = word.to_ascii_lowercase();
+        let pos = closed_class(&word)
+            .or_else(|| suffix_class(&word))
+            .unwrap_or(match self.previous {
+                // "to view", "they view", "will view"
+                _ if self.previous_to => PartOfSpeech::Verb,
+                Some(PartOfSpeech::Pronoun) | Some(PartOfSpeech::Auxiliary) => PartOfSpeech::Verb,
+                // "the view", "of view", and everything else
+                _ => PartOfSpeech::Noun,
+            });
+        self.previous = Some(pos);
+        self.previous_to = word == "to";
+        pos
+    }
+}
@@ -0,0 +1,70 @@
+use regex::Regex;
+use serde_derive::Deserialize;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+// short function words that make text hard to read when replaced
+pub static STOPWORDS: &[&str] = &[
+    "a", "about", "am", "an", "and", "are", "as", "at", "be", "been", "but", "by", "can", "could",
+    "did", "do", "does", "for", "from", "had", "has", "have", "he", "her", "him", "his", "i", "if",
+    "in", "into", "is", "it", "it's", "its", "may", "me", "might", "must", "my", "no", "nor",
+    "not", "of", "on", "or", "our", "out", "over", "shall", "she", "should", "so", "than", "that",
+    "the", "their", "them", "then", "these", "they", "this", "those", "to", "up", "us", "was",
+    "we", "were", "will", "with", "would", "you", "your",
+];
+
+// how words are compared against stopwords and protected words, both when they
+// are added and when they are looked up, so "Über" matches "über"
+pub fn fold_case(word: &str) -> String {
+    word.to_lowercase()
+}
+
+pub fn is_stopword(word: &str) -> bool {
+    STOPWORDS.contains(&fold_case(word).as_str())
+}
+
+// words that must never be replaced, either exactly (ignoring case) or by regex
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "ProtectedSpec")]
+pub struct Protected {
+    words: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+impl Protected {
+    // patterns have to match the whole word, so "re.*" protects "react" but not "are"
+    pub fn new<W, P>(words: W, patterns: P) -> Result<Self, regex::Error>
+    where
+        W: IntoIterator,
+        W::Item: AsRef<str>,
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+    {
+        Ok(Protected {
+            words: words
+                .into_iter()
+                .map(|word| fold_case(word.as_ref()))
+                .collect(),
+            patterns: patterns
+                .into_iter()
+                .map(|pattern| Regex::new(&format!("^(?:{})$", pattern.as_ref())))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&fold_case(word))
+            || self.patterns.iter().any(|pattern| pattern.is_match(word))
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ProtectedSpec {
+    words: Vec<String>,
+    patterns: Vec<String>,
+}
+impl TryFrom<ProtectedSpec> for Protected {
+    type Error = regex::Error;
+    fn try_from(spec: ProtectedSpec) -> Result<Self, Self::Error> {
+        Protected::new(spec.words, spec.patterns)
+    }
+}
@@ -0,0 +1,196 @@
+use super::{
+    is_word_char, replace_part, split_words, tag_part, ReplaceOptions, ReplaceState, Replacement,
+    Replacer, Tagger,
+};
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+// replaces text that arrives in chunks, such as a large file, giving back
+// replacements as soon as the words they are made from are known to be complete
+pub struct ReplaceStream<'a> {
+    replacer: &'a Replacer,
+    options: &'a ReplaceOptions,
+    state: ReplaceState<'a>,
+    tagger: Tagger,
+    // the last word or gap seen, which could still continue in the next chunk
+    pending: String,
+}
+impl<'a> ReplaceStream<'a> {
+    pub fn push(&mut self, chunk: &str) -> Vec<Replacement> {
+        self.pending.push_str(chunk);
+        let mut parts = split_words(&self.pending);
+        let last = match parts.pop() {
+            Some((_, last)) => last,
+            None => return Vec::new(),
+        };
+        self.pending = last;
+        self.replace_parts(parts)
+    }
+
+    // replaces whatever is left once there are no more chunks
+    pub fn finish(mut self) -> Vec<Replacement> {
+        self.flush()
+    }
+
+    // replaces the last word or gap seen, as if the next chunk won't continue it
+    fn flush(&mut self) -> Vec<Replacement> {
+        let parts = split_words(&mem::take(&mut self.pending));
+        self.replace_parts(parts)
+    }
+
+    fn replace_parts(&mut self, parts: Vec<(bool, String)>) -> Vec<Replacement> {
+        let (replacer, options) = (self.replacer, self.options);
+        let tagger = &mut self.tagger;
+        let state = &mut self.state;
+        parts
+            .into_iter()
+            .map(|(is_word, part)| {
+                replace_part(
+                    is_word,
+                    part,
+                    tagger,
+                    &|word| replacer.is_protected(word, options),
+                    &mut |word, part_of_speech| state.replace_word(word, part_of_speech),
+                )
+            })
+            .collect()
+    }
+}
+
+// an edit of some text: `deleted` bytes at byte `offset` were replaced with `inserted`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub offset: usize,
+    pub deleted: usize,
+    pub inserted: String,
+}
+
+// an edit that goes past the end of the text it edits
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditOutOfRange {
+    pub end: usize,
+    pub len: usize,
+}
+impl fmt::Display for EditOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "edit ends at byte {} of a text that is {} bytes long",
+            self.end, self.len
+        )
+    }
+}
+impl Error for EditOutOfRange {}
+
+// the replacements `start..start + removed` have to be swapped for `replacements`
+#[derive(Debug)]
+pub struct Splice {
+    pub start: usize,
+    pub removed: usize,
+    pub replacements: Vec<Replacement>,
+}
+impl Splice {
+    pub fn apply(self, replacements: &mut Vec<Replacement>) {
+        replacements.splice(self.start..self.start + self.removed, self.replacements);
+    }
+}
+
+impl Replacer {
+    pub fn stream<'a>(&'a self, options: &'a ReplaceOptions) -> ReplaceStream<'a> {
+        ReplaceStream {
+            replacer: self,
+            options,
+            state: ReplaceState::new(self, options),
+            tagger: Tagger::new(),
+            pending: String::new(),
+        }
+    }
+
+    // finds what changes in `previous`, the replacements of some text, after an edit of that
+    // text, only replacing the words around the edit again, and the words after it whose
+    // part of speech changes because of it.
+    // sampling and variety start over at the edit, so they can differ from a full `replace_with`
+    pub fn replace_edit(
+        &self,
+        previous: &[Replacement],
+        edit: &Edit,
+        options: &ReplaceOptions,
+    ) -> Result<Splice, EditOutOfRange> {
+        // byte offset of where each replacement starts in the old text
+        let starts: Vec<usize> = previous
+            .iter()
+            .scan(0, |offset, replacement| {
+                let start = *offset;
+                *offset += replacement.original().len();
+                Some(start)
+            })
+            .collect();
+        let old_len =
+            starts.last().unwrap_or(&0) + previous.last().map_or(0, |r| r.original().len());
+        if edit.offset + edit.deleted > old_len {
+            return Err(EditOutOfRange {
+                end: edit.offset + edit.deleted,
+                len: old_len,
+            });
+        }
+        if previous.is_empty() {
+            return Ok(Splice {
+                start: 0,
+                removed: 0,
+                replacements: self.replace_with(&edit.inserted, options),
+            });
+        }
+
+        // take the replacements touching the edit on both sides, because the
+        // edit could join the words next to it, like typing a letter after a word
+        let containing = |byte: usize| starts.partition_point(|start| *start <= byte) - 1;
+        let first = containing(edit.offset.saturating_sub(1));
+        let mut last = containing((edit.offset + edit.deleted).min(old_len - 1));
+        let old: String = previous[first..=last]
+            .iter()
+            .map(|replacement| replacement.original())
+            .collect();
+        let local = edit.offset - starts[first];
+        let text = format!(
+            "{}{}{}",
+            &old[..local],
+            edit.inserted,
+            &old[local + edit.deleted..]
+        );
+
+        let mut stream = self.stream(options);
+        // the word before the edit is context for guessing parts of speech
+        if let Some(word) = previous[..first]
+            .iter()
+            .map(|replacement| replacement.original())
+            .rfind(|part| part.starts_with(is_word_char))
+        {
+            stream.tagger.tag(word);
+        }
+        // tags the text from before the edit as well, to know which tags change after it
+        let mut old_tagger = stream.tagger.clone();
+        let tag_old = |tagger: &mut Tagger, replacement: &Replacement| {
+            let part = replacement.original();
+            tag_part(tagger, part.starts_with(is_word_char), part);
+        };
+        for replacement in &previous[first..=last] {
+            tag_old(&mut old_tagger, replacement);
+        }
+        let mut replacements = stream.push(&text);
+        replacements.extend(stream.flush());
+        // the tagger only looks at the word before, so once a word after the edit gets
+        // the tag it had before, so do all the words after it
+        while stream.tagger != old_tagger && last + 1 < previous.len() {
+            last += 1;
+            tag_old(&mut old_tagger, &previous[last]);
+            replacements.extend(stream.push(previous[last].original()));
+            replacements.extend(stream.flush());
+        }
+        Ok(Splice {
+            start: first,
+            removed: last - first + 1,
+            replacements,
+        })
+    }
+}
@@ -0,0 +1,39 @@
+//! Checks the British English dictionary preload derives from cmudict.
+
+#[cfg(feature = "british")]
+use vite_wasm_functions::pronounce::Accent;
+use vite_wasm_functions::pronounce::PronounceBuilder;
+
+#[cfg(feature = "british")]
+#[test]
+fn british_follows_the_rp_rules() {
+    let american = PronounceBuilder::with_accent(Accent::American);
+    let british = PronounceBuilder::with_accent(Accent::British);
+    let ipa = |builder: &PronounceBuilder, word| builder.to_ipa(&builder.pronounce(word));
+    // non-rhotic R, but still said before a vowel
+    assert_eq!(ipa(&american, "car"), "kɑɹ");
+    assert_eq!(ipa(&british, "car"), "kɑ");
+    assert_eq!(ipa(&british, "near"), "nɪə");
+    assert_eq!(ipa(&british, "sofa"), "soʊfə");
+    assert_eq!(ipa(&british, "very"), "vɛɹi");
+    // BATH and TRAP
+    assert_eq!(ipa(&british, "bath"), "bɑθ");
+    assert_eq!(ipa(&british, "cat"), "kæt");
+    // LOT and THOUGHT
+    assert_eq!(ipa(&british, "hot"), "hɒt");
+    assert_eq!(ipa(&british, "thought"), "θɔt");
+    // words without any of them are the same
+    assert_eq!(ipa(&british, "react"), ipa(&american, "react"));
+}
+
+#[test]
+fn dictionaries_can_be_added() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../preload/output/cmudict-rp.bin"
+    );
+    let mut builder = PronounceBuilder::new();
+    builder.add_dict(std::fs::read(path).unwrap()).unwrap();
+    assert_eq!(builder.to_ipa(&builder.pronounce("car")), "kɑ");
+    assert!(builder.add_dict(b"not a dictionary".to_vec()).is_err());
+}
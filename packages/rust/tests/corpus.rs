@@ -0,0 +1,153 @@
+//! Evaluates `Replacer::replace` against the golden corpus in `tests/corpus/golden.txt`.
+//!
+//! Run with `cargo test --test corpus -- --nocapture` to see the metrics report.
+
+use vite_wasm_functions::framework_data::read_frameworks;
+use vite_wasm_functions::replacer::{replace_words, ReplaceMode, Replacement, Replacer};
+
+static GOLDEN: &str = include_str!("corpus/golden.txt");
+
+// raise these when the replacer gets better, so it can't silently get worse again
+const MIN_PRECISION: f32 = 0.7;
+const MIN_RECALL: f32 = 0.85;
+
+struct Case<'a> {
+    threshold: f32,
+    star_threshold: u32,
+    input: &'a str,
+    // the framework each token of the input should be replaced with, if any
+    expected: Vec<Option<String>>,
+}
+
+fn tokens(input: &str) -> Vec<String> {
+    replace_words(input, |_| false, |_, _| None)
+        .into_iter()
+        .map(|replacement| match replacement {
+            Replacement::Keep(part) => part,
+            Replacement::Replace(_, replaced) => replaced.original,
+        })
+        .collect()
+}
+
+// walks the input tokens and the expected output together, to find out which tokens were bracketed
+fn parse_expected(input: &str, expected: &str) -> Vec<Option<String>> {
+    let mut rest = expected;
+    let ret = tokens(input)
+        .into_iter()
+        .map(|token| {
+            if let Some(bracketed) = rest.strip_prefix('[') {
+                let end = bracketed
+                    .find(']')
+                    .unwrap_or_else(|| panic!("unclosed bracket in: {}", expected));
+                rest = &bracketed[end + 1..];
+                Some(bracketed[..end].to_owned())
+            } else {
+                rest = rest.strip_prefix(token.as_str()).unwrap_or_else(|| {
+                    panic!("expected {:?} does not match input {:?}", expected, input)
+                });
+                None
+            }
+        })
+        .collect();
+    assert!(rest.is_empty(), "trailing text in expected: {:?}", expected);
+    ret
+}
+
+fn read_corpus() -> Vec<Case<'static>> {
+    GOLDEN
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.split(" | ");
+            let mut settings = parts.next().unwrap().split_whitespace();
+            let threshold = settings.next().unwrap().parse().unwrap();
+            let star_threshold = settings.next().unwrap().parse().unwrap();
+            let input = parts.next().unwrap();
+            let expected = parts.next().unwrap();
+            Case {
+                threshold,
+                star_threshold,
+                input,
+                expected: parse_expected(input, expected),
+            }
+        })
+        .collect()
+}
+
+fn render(tokens: &[String], replaced: &[Option<String>]) -> String {
+    tokens
+        .iter()
+        .zip(replaced)
+        .map(|(token, replaced)| match replaced {
+            Some(framework) => format!("[{}]", framework),
+            None => token.clone(),
+        })
+        .collect()
+}
+
+#[test]
+fn golden_corpus() {
+    let rep = Replacer::new(read_frameworks());
+    let (mut true_pos, mut false_pos, mut false_neg) = (0, 0, 0);
+    let mut exact = 0;
+    let cases = read_corpus();
+    for case in cases.iter() {
+        let predicted: Vec<Option<String>> = rep
+            .replace(
+                case.input,
+                case.threshold,
+                case.star_threshold,
+                ReplaceMode::Sound,
+            )
+            .into_iter()
+            .map(|replacement| match replacement {
+                Replacement::Replace(framework, _) => Some(framework),
+                Replacement::Keep(_) => None,
+            })
+            .collect();
+        assert_eq!(predicted.len(), case.expected.len());
+
+        for (predicted, expected) in predicted.iter().zip(case.expected.iter()) {
+            match (predicted, expected) {
+                (Some(predicted), Some(expected)) if predicted == expected => true_pos += 1,
+                (None, None) => {}
+                // a wrong framework is both a false positive and a false negative
+                _ => {
+                    false_pos += predicted.is_some() as u32;
+                    false_neg += expected.is_some() as u32;
+                }
+            }
+        }
+        if predicted == case.expected {
+            exact += 1;
+        } else {
+            let tokens = tokens(case.input);
+            println!("mismatch at {} {}:", case.threshold, case.star_threshold);
+            println!("  expected:  {}", render(&tokens, &case.expected));
+            println!("  predicted: {}", render(&tokens, &predicted));
+        }
+    }
+
+    let precision = true_pos as f32 / (true_pos + false_pos).max(1) as f32;
+    let recall = true_pos as f32 / (true_pos + false_neg).max(1) as f32;
+    println!(
+        "exact: {}/{}, precision: {:.3}, recall: {:.3}",
+        exact,
+        cases.len(),
+        precision,
+        recall
+    );
+    assert!(
+        precision >= MIN_PRECISION,
+        "precision regressed: {:.3} < {}",
+        precision,
+        MIN_PRECISION
+    );
+    assert!(
+        recall >= MIN_RECALL,
+        "recall regressed: {:.3} < {}",
+        recall,
+        MIN_RECALL
+    );
+}
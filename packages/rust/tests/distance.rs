@@ -0,0 +1,112 @@
+//! Property tests checking that `Pronounce::distance` behaves like a dissimilarity.
+
+use proptest::prelude::*;
+use std::sync::OnceLock;
+use vite_wasm_functions::pronounce::{
+    DistanceConfig, DistanceModel, PhoneDistances, Pronounce, PronounceBuilder,
+};
+
+fn builder() -> &'static PronounceBuilder {
+    static BUILDER: OnceLock<PronounceBuilder> = OnceLock::new();
+    BUILDER.get_or_init(PronounceBuilder::new)
+}
+
+fn distances() -> &'static PhoneDistances {
+    static DISTANCES: OnceLock<PhoneDistances> = OnceLock::new();
+    DISTANCES.get_or_init(|| builder().distances(&DistanceConfig::default()))
+}
+
+fn phone(phones: impl Iterator<Item = &'static str>) -> impl Strategy<Value = String> {
+    // cmudict marks vowels with stress, the replacer should ignore it
+    (
+        prop::sample::select(phones.collect::<Vec<_>>()),
+        prop::option::of(0..3u8),
+    )
+        .prop_map(|(phone, stress)| match stress {
+            Some(stress) => format!("{}{}", phone, stress),
+            None => phone.to_owned(),
+        })
+}
+
+// random arpabet strings with the given number of syllables, each made of
+// consonants around a vowel, using phones from the vowel and consonant tables
+fn word(syllables: usize) -> impl Strategy<Value = String> {
+    let consonants = || prop::collection::vec(phone(builder().consonants()), 0..5);
+    let syllable =
+        (consonants(), phone(builder().vowels()), consonants()).prop_map(|(onset, vowel, coda)| {
+            onset
+                .into_iter()
+                .chain(std::iter::once(vowel))
+                .chain(coda)
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+    prop::collection::vec(syllable, syllables).prop_map(|syllables| syllables.join(" "))
+}
+
+// any arpabet string, including ones without vowels
+fn arpabet(max_len: usize) -> impl Strategy<Value = String> {
+    let any_phone = prop_oneof![phone(builder().vowels()), phone(builder().consonants())];
+    prop::collection::vec(any_phone, 0..max_len).prop_map(|phones| phones.join(" "))
+}
+
+// two words with the same number of syllables
+fn word_pair() -> impl Strategy<Value = (String, String)> {
+    (1..5usize).prop_flat_map(|syllables| (word(syllables), word(syllables)))
+}
+
+fn pronounce(arpabet: &str) -> Pronounce {
+    builder().pronounce_from_arpabet(arpabet).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn identity_is_zero(a in (1..5usize).prop_flat_map(word)) {
+        let a = pronounce(&a);
+        prop_assert_eq!(a.distance(&a, distances()), 0.0);
+    }
+
+    #[test]
+    fn symmetric((a, b) in word_pair()) {
+        let (a, b) = (pronounce(&a), pronounce(&b));
+        prop_assert_eq!(a.distance(&b, distances()), b.distance(&a, distances()));
+    }
+
+    #[test]
+    fn finite_for_equal_syllables((a, b) in word_pair()) {
+        let (a, b) = (pronounce(&a), pronounce(&b));
+        let distance = a.distance(&b, distances());
+        prop_assert!((0.0..f32::MAX).contains(&distance));
+    }
+
+    #[test]
+    fn never_panics(a in arpabet(24), b in arpabet(24)) {
+        pronounce(&a).distance(&pronounce(&b), distances());
+    }
+}
+
+#[test]
+fn features_tell_voicing_apart() {
+    let config = DistanceConfig {
+        model: DistanceModel::Features,
+        ..DistanceConfig::default()
+    };
+    let distances = builder().distances(&config);
+    let [bat, pat, sat] = ["bat", "pat", "sat"].map(|word| builder().pronounce(word));
+    assert!(bat.distance(&pat, &distances) > 0.0);
+    assert!(bat.distance(&pat, &distances) < bat.distance(&sat, &distances));
+}
+
+#[test]
+fn features_tell_diphthongs_apart_by_their_offglide() {
+    let config = DistanceConfig {
+        model: DistanceModel::Features,
+        ..DistanceConfig::default()
+    };
+    let distances = builder().distances(&config);
+    let phone = |arpabet| builder().phone_from_arpabet(arpabet).unwrap();
+    // they start alike, but glide to the front and the back
+    assert!(distances.get(phone("AW"), phone("AY")) > 0.0);
+    assert!(distances.get(phone("AO"), phone("OY")) > 0.0);
+    assert_eq!(distances.get(phone("AY"), phone("AY")), 0.0);
+}
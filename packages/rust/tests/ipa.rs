@@ -0,0 +1,79 @@
+//! Checks that IPA is read and written with the arpabet conversion tables.
+
+use vite_wasm_functions::pronounce::{Phone, PronounceBuilder};
+
+fn phones(builder: &PronounceBuilder, pronounce: &str) -> Vec<Phone> {
+    builder
+        .pronounce_from_arpabet(pronounce)
+        .unwrap()
+        .phones()
+        .collect()
+}
+
+#[test]
+fn renders_words_as_ipa() {
+    let builder = PronounceBuilder::new();
+    assert_eq!(builder.to_ipa(&builder.pronounce("react")), "ɹiækt");
+    assert_eq!(builder.to_ipa(&builder.pronounce("notaword")), "");
+}
+
+#[test]
+fn reads_ipa_like_arpabet() {
+    let builder = PronounceBuilder::new();
+    let arpabet = phones(&builder, "R IY0 AE1 K T");
+    assert_eq!(phones(&builder, "ɹiækt"), arpabet);
+    // stress, slashes, spaces and ascii r are ignored or read as the usual letters
+    assert_eq!(phones(&builder, "/ri ˈækt/"), arpabet);
+    // diphthongs and affricates are one phone
+    assert_eq!(phones(&builder, "tʃaɪ"), phones(&builder, "CH AY1"));
+}
+
+#[test]
+fn ipa_round_trips() {
+    let builder = PronounceBuilder::new();
+    for word in ["javascript", "framework", "angular", "svelte", "beautiful"] {
+        let pronounce = builder.pronounce(word);
+        let ipa = builder.to_ipa(&pronounce);
+        assert_eq!(
+            phones(&builder, &ipa),
+            pronounce.phones().collect::<Vec<_>>(),
+            "{} ({})",
+            word,
+            ipa
+        );
+    }
+}
+
+#[test]
+fn reads_common_ipa_letters_outside_the_charts() {
+    let builder = PronounceBuilder::new();
+    // plain vowel letters are the arpabet vowel they start, unless a glide follows
+    assert_eq!(phones(&builder, "pe"), phones(&builder, "P EY1"));
+    assert_eq!(phones(&builder, "peɪ"), phones(&builder, "P EY1"));
+    assert_eq!(phones(&builder, "ɡo"), phones(&builder, "G OW1"));
+    assert_eq!(phones(&builder, "ka"), phones(&builder, "K AA1"));
+    assert_eq!(phones(&builder, "kaɪ"), phones(&builder, "K AY1"));
+    assert_eq!(phones(&builder, "kɐt"), phones(&builder, "K AH1 T"));
+    assert_eq!(phones(&builder, "fiɫ"), phones(&builder, "F IY1 L"));
+    // anything else isn't a pronunciation, rather than a panic
+    assert!(builder.pronounce_from_arpabet("ʘ").is_none());
+    assert!(builder.pronounce_from_arpabet("XX1").is_none());
+}
+
+#[test]
+fn reads_arpabet_in_any_case() {
+    let builder = PronounceBuilder::new();
+    let arpabet = phones(&builder, "R IY0 AE1 K T");
+    assert_eq!(phones(&builder, "r iy0 ae1 k t"), arpabet);
+    assert_eq!(phones(&builder, "R iy0 Ae1 k T"), arpabet);
+}
+
+#[test]
+fn writes_the_nurse_vowel_with_its_r_color() {
+    let builder = PronounceBuilder::new();
+    assert_eq!(builder.to_ipa(&builder.pronounce("bird")), "bɝd");
+    let arpabet = phones(&builder, "B ER1 D");
+    for ipa in ["bɝd", "bɚd", "bɜd"] {
+        assert_eq!(phones(&builder, ipa), arpabet, "{}", ipa);
+    }
+}
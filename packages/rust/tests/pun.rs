@@ -0,0 +1,41 @@
+//! Checks that `PunFinder` finds words and phrases that sound like frameworks.
+
+use vite_wasm_functions::framework_data::read_frameworks;
+use vite_wasm_functions::pronounce::{DistanceConfig, PronounceBuilder};
+use vite_wasm_functions::pun::{parse_phrases, read_phrases, PunFinder};
+use vite_wasm_functions::replacer::Replacer;
+
+#[test]
+fn finds_words_and_phrases() {
+    let builder = PronounceBuilder::new();
+    let finder = PunFinder::new(&builder, read_phrases());
+    let replacer = Replacer::new(read_frameworks());
+
+    let puns = replacer.puns(&finder, "Vue", 0.5, 5);
+    assert_eq!(puns[0], (0.0, "view"));
+    assert!(puns.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    // the framework's own name isn't a pun
+    let puns = replacer.puns(&finder, "next", 0.5, 100);
+    assert!(puns.iter().all(|(_, text)| *text != "next"));
+    assert!(puns.iter().any(|(_, text)| *text == "text"));
+    // it is left out before the limit, so a limit of one still finds one
+    let puns = replacer.puns(&finder, "next", 0.5, 1);
+    assert_eq!(puns.len(), 1);
+    assert_ne!(puns[0].1, "next");
+
+    let distances = builder.distances(&DistanceConfig::default());
+    let time_flies = builder.pronounce_from_arpabet("T AY1 M F L AY1 Z").unwrap();
+    assert_eq!(
+        finder.find(&time_flies, &distances, 0.1, 1),
+        [(0.0, "time flies")]
+    );
+}
+
+#[test]
+fn malformed_phrases_are_an_error() {
+    assert_eq!(
+        parse_phrases("time flies,T AY1 M F L AY1 Z\n").unwrap(),
+        [("time flies".to_owned(), "T AY1 M F L AY1 Z".to_owned())]
+    );
+    assert!(parse_phrases("time flies\n").is_err());
+}
@@ -0,0 +1,468 @@
+//! Tests for the options of `Replacer::replace_with`.
+
+use std::sync::OnceLock;
+use vite_wasm_functions::framework_data::{parse_frameworks, read_frameworks};
+use vite_wasm_functions::pronounce::{
+    DistanceConfig, PhoneTables, PronounceBuilder, SpanishSource,
+};
+use vite_wasm_functions::replacer::{
+    Edit, PartOfSpeech, Protected, ReplaceMode, ReplaceOptions, Replacement, Replacer, Sampling,
+    Tagger, Variety,
+};
+use vite_wasm_functions::LoadError;
+
+static TEXT: &str = "I react to the next fact with tact, the best nest is in the west.";
+
+fn replacer() -> &'static Replacer {
+    static REPLACER: OnceLock<Replacer> = OnceLock::new();
+    REPLACER.get_or_init(|| Replacer::new(read_frameworks()))
+}
+
+fn replaced(replacements: Vec<Replacement>) -> Vec<Option<String>> {
+    replacements
+        .into_iter()
+        .map(|replacement| match replacement {
+            Replacement::Replace(word, _) => Some(word),
+            Replacement::Keep(_) => None,
+        })
+        .collect()
+}
+
+fn sampled(temperature: f32, seed: u64) -> Vec<Option<String>> {
+    let options = ReplaceOptions {
+        threshold: 1.0,
+        sampling: Some(Sampling { temperature, seed }),
+        ..ReplaceOptions::default()
+    };
+    replaced(replacer().replace_with(TEXT, &options))
+}
+
+#[test]
+fn sampling_is_reproducible() {
+    assert_eq!(sampled(0.5, 7), sampled(0.5, 7));
+    // some seed should give a different result, otherwise it isn't sampling
+    assert!((0..10).any(|seed| sampled(0.5, seed) != sampled(0.5, 7)));
+}
+
+#[test]
+fn zero_temperature_picks_the_best() {
+    // without a star bonus, so only the distance decides
+    let options = ReplaceOptions {
+        threshold: 1.0,
+        star_weight: 0.0,
+        ..ReplaceOptions::default()
+    };
+    let best = replaced(replacer().replace_with(TEXT, &options));
+    let sampling = ReplaceOptions {
+        sampling: Some(Sampling {
+            temperature: 0.0,
+            seed: 3,
+        }),
+        ..options
+    };
+    assert_eq!(replaced(replacer().replace_with(TEXT, &sampling)), best);
+}
+
+fn weighted(vocab: &[(&str, &str, u64)], star_weight: f32) -> Option<String> {
+    let rep = Replacer::new(
+        vocab
+            .iter()
+            .map(|(name, arpabet, stars)| (name.to_string(), arpabet.to_string(), *stars))
+            .collect(),
+    );
+    let options = ReplaceOptions {
+        threshold: 0.5,
+        star_threshold: 0,
+        star_weight,
+        ..ReplaceOptions::default()
+    };
+    replaced(rep.replace_with("nest", &options)).remove(0)
+}
+
+#[test]
+fn stars_break_close_ties() {
+    let vocab = [
+        ("Obscure", "N EH1 Z T", 10),
+        ("Popular", "N EH1 Z D", 200_000),
+    ];
+    assert_eq!(weighted(&vocab, 0.0).as_deref(), Some("Obscure"));
+    assert_eq!(weighted(&vocab, 0.05).as_deref(), Some("Popular"));
+}
+
+#[test]
+fn stars_dont_beat_a_much_better_fit() {
+    let vocab = [
+        ("Exact", "N EH1 S T", 10),
+        ("Popular", "B EH1 S T", 200_000),
+    ];
+    assert_eq!(weighted(&vocab, 0.05).as_deref(), Some("Exact"));
+}
+
+#[test]
+fn unpopular_frameworks_are_offered_by_default() {
+    let rep = Replacer::new(vec![
+        ("Exact".to_owned(), "N EH1 S T".to_owned(), 10),
+        ("Popular".to_owned(), "B EH1 S T".to_owned(), 200_000),
+    ]);
+    // no star threshold, popularity only ranks the candidates through `star_weight`
+    let options = ReplaceOptions {
+        threshold: 0.5,
+        ..ReplaceOptions::default()
+    };
+    assert_eq!(
+        replaced(rep.replace_with("nest", &options))[0].as_deref(),
+        Some("Exact")
+    );
+}
+
+fn varied(text: &str, window: Option<usize>) -> Vec<String> {
+    let options = ReplaceOptions {
+        threshold: 0.6,
+        star_threshold: 0,
+        variety: Some(Variety {
+            penalty: 1.0,
+            window,
+        }),
+        ..ReplaceOptions::default()
+    };
+    replaced(replacer().replace_with(text, &options))
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[test]
+fn variety_avoids_repeats() {
+    assert_eq!(
+        replaced(replacer().replace("best nest west", 0.6, 0, ReplaceMode::Sound))
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>(),
+        ["Nest", "Nest", "Nest"]
+    );
+    let frameworks = varied("best nest west", None);
+    assert_eq!(frameworks.len(), 3);
+    assert_ne!(frameworks[0], frameworks[1]);
+    assert_ne!(frameworks[1], frameworks[2]);
+}
+
+#[test]
+fn variety_window_forgets() {
+    // with a window of one word, "the" is enough to forget about the first Nest
+    assert_eq!(varied("nest the nest", Some(1)), ["Nest", "Nest"]);
+    assert_ne!(varied("nest the nest", Some(2))[1], "Nest");
+}
+
+fn kept(text: &str, options: &ReplaceOptions) -> Vec<String> {
+    replacer()
+        .replace_with(text, options)
+        .into_iter()
+        .filter_map(|replacement| match replacement {
+            Replacement::Keep(word) if !word.trim().is_empty() => Some(word),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn stopwords_are_kept() {
+    let options = ReplaceOptions {
+        threshold: 100.0,
+        star_threshold: 0,
+        ..ReplaceOptions::default()
+    };
+    assert!(kept("the view of a nest", &options).is_empty());
+    let options = ReplaceOptions {
+        stopwords: true,
+        ..options
+    };
+    assert_eq!(kept("the view of a nest", &options), ["the", "of", "a"]);
+}
+
+#[test]
+fn added_stopwords_are_kept_in_any_case() {
+    let mut replacer = Replacer::new(read_frameworks());
+    // read as Spanish "biú" sounds like Vue, and only an accent-aware lowercase
+    // turns "BIÚ" into it
+    replacer.add_fallback_source(SpanishSource);
+    replacer.add_stopwords(["BIÚ"]);
+    let options = ReplaceOptions {
+        threshold: 0.5,
+        star_threshold: 0,
+        ..ReplaceOptions::default()
+    };
+    let replaced_with = |options: &ReplaceOptions| replaced(replacer.replace_with("biú", options));
+    assert_eq!(replaced_with(&options), [Some("Vue".to_owned())]);
+    let options = ReplaceOptions {
+        stopwords: true,
+        ..options
+    };
+    assert_eq!(replaced_with(&options), [None]);
+}
+
+#[test]
+fn protected_words_and_patterns_are_kept() {
+    let options = ReplaceOptions {
+        threshold: 100.0,
+        star_threshold: 0,
+        protected: Protected::new(["View"], ["ne.*", "[A-Z]+"]).unwrap(),
+        ..ReplaceOptions::default()
+    };
+    assert_eq!(
+        kept("the view of a nest NASA neon", &options),
+        ["view", "nest", "NASA", "neon"]
+    );
+    assert!(Protected::new(Vec::<String>::new(), ["("]).is_err());
+}
+
+#[test]
+fn tagger_guesses_parts_of_speech() {
+    let mut tagger = Tagger::new();
+    // "quick" would be a noun, since adjectives are only known by their suffixes
+    let tags: Vec<_> = "they view the careful nation from a distance happily"
+        .split(' ')
+        .map(|word| tagger.tag(word))
+        .collect();
+    use PartOfSpeech::*;
+    assert_eq!(
+        tags,
+        [
+            Pronoun,
+            Verb,
+            Determiner,
+            Adjective,
+            Noun,
+            Preposition,
+            Determiner,
+            Noun,
+            Adverb
+        ]
+    );
+}
+
+#[test]
+fn parts_of_speech_restrict_replacement() {
+    let options = ReplaceOptions {
+        threshold: 100.0,
+        star_threshold: 0,
+        parts_of_speech: Some(vec![PartOfSpeech::Noun]),
+        ..ReplaceOptions::default()
+    };
+    let replacements = replacer().replace_with("the view of a nest", &options);
+    let replaced: Vec<_> = replacements
+        .into_iter()
+        .filter_map(|replacement| match replacement {
+            Replacement::Replace(_, replaced) => Some(replaced),
+            Replacement::Keep(_) => None,
+        })
+        .collect();
+    assert_eq!(replaced.len(), 2);
+    assert_eq!(replaced[0].original, "view");
+    assert_eq!(replaced[1].original, "nest");
+    assert!(replaced
+        .iter()
+        .all(|replaced| replaced.part_of_speech == PartOfSpeech::Noun));
+}
+
+static LONG_TEXT: &str = "Change your point of view. I react to the next fact with tact, \
+    the best nest is in the west. Express yourself with angular shapes!";
+
+fn long_options() -> ReplaceOptions {
+    ReplaceOptions {
+        threshold: 0.5,
+        ..ReplaceOptions::default()
+    }
+}
+
+#[test]
+fn stream_matches_replace() {
+    let options = long_options();
+    let expected = replacer().replace_with(LONG_TEXT, &options);
+    for chunk_size in [1, 2, 5, 13, 1000] {
+        let mut stream = replacer().stream(&options);
+        let mut streamed = Vec::new();
+        let chars: Vec<char> = LONG_TEXT.chars().collect();
+        for chunk in chars.chunks(chunk_size) {
+            streamed.extend(stream.push(&chunk.iter().collect::<String>()));
+        }
+        streamed.extend(stream.finish());
+        assert_eq!(streamed, expected, "chunk size {}", chunk_size);
+    }
+}
+
+#[test]
+fn edits_match_replace() {
+    let options = long_options();
+    let edits = [
+        // typing at the end of a word, in the middle of one, and at the end of the text
+        (LONG_TEXT.find("view").unwrap() + 4, 0, "s"),
+        (LONG_TEXT.find("fact").unwrap() + 2, 0, "c"),
+        (LONG_TEXT.len(), 0, " Nest"),
+        // deleting the space between two words, and replacing a whole sentence
+        (LONG_TEXT.find(" nest").unwrap(), 1, ""),
+        (0, LONG_TEXT.find('.').unwrap(), "The best view"),
+        (0, LONG_TEXT.len(), ""),
+    ];
+    for (offset, deleted, inserted) in edits {
+        let mut replacements = replacer().replace_with(LONG_TEXT, &options);
+        let edit = Edit {
+            offset,
+            deleted,
+            inserted: inserted.to_owned(),
+        };
+        replacer()
+            .replace_edit(&replacements, &edit, &options)
+            .unwrap()
+            .apply(&mut replacements);
+
+        let mut text = LONG_TEXT.to_owned();
+        text.replace_range(offset..offset + deleted, inserted);
+        assert_eq!(
+            replacements,
+            replacer().replace_with(&text, &options),
+            "{:?}",
+            edit
+        );
+    }
+}
+
+#[test]
+fn edits_tag_the_words_after_them_again() {
+    let options = ReplaceOptions {
+        threshold: 100.0,
+        star_threshold: 0,
+        parts_of_speech: Some(vec![PartOfSpeech::Noun]),
+        ..ReplaceOptions::default()
+    };
+    // "view" is a verb after "they" but a noun after "the", so it is only replaced after
+    let text = "they view it";
+    let mut replacements = replacer().replace_with(text, &options);
+    let edit = Edit {
+        offset: 3,
+        deleted: 1,
+        inserted: String::new(),
+    };
+    replacer()
+        .replace_edit(&replacements, &edit, &options)
+        .unwrap()
+        .apply(&mut replacements);
+    assert_eq!(
+        replacements,
+        replacer().replace_with("the view it", &options)
+    );
+    assert!(matches!(&replacements[2], Replacement::Replace(..)));
+
+    let past_the_end = Edit {
+        offset: text.len(),
+        deleted: 1,
+        inserted: String::new(),
+    };
+    assert!(replacer()
+        .replace_edit(&replacements, &past_the_end, &options)
+        .is_err());
+}
+
+#[test]
+fn from_bytes_matches_embedded() {
+    let output = concat!(env!("CARGO_MANIFEST_DIR"), "/../preload/output");
+    let read = |path: &str| std::fs::read_to_string(format!("{}/{}", output, path)).unwrap();
+    let tables = PhoneTables {
+        vowels: read("pronounce/vowels.csv"),
+        consonants: read("pronounce/consonants.csv"),
+        features: read("pronounce/features.csv"),
+        ipa: read("pronounce/ipa.csv"),
+    };
+    let builder = PronounceBuilder::from_bytes(
+        std::fs::read(format!("{}/cmudict.bin", output)).unwrap(),
+        &tables,
+    )
+    .unwrap();
+    let loaded = Replacer::with_builder(
+        parse_frameworks(&read("frameworks.csv")).unwrap(),
+        builder,
+        DistanceConfig::default(),
+    );
+    assert_eq!(
+        loaded.replace(TEXT, 0.5, 100, ReplaceMode::Sound),
+        replacer().replace(TEXT, 0.5, 100, ReplaceMode::Sound)
+    );
+    assert!(matches!(
+        PronounceBuilder::from_bytes(b"not a dictionary".to_vec(), &PhoneTables::default()),
+        Err(LoadError::InvalidData(_))
+    ));
+    // broken tables are errors rather than panics
+    let dict = std::fs::read(format!("{}/cmudict.bin", output)).unwrap();
+    let broken = PhoneTables {
+        vowels: "AA,not a number,0".to_owned(),
+        ..tables.clone()
+    };
+    assert!(PronounceBuilder::from_bytes(dict.clone(), &broken).is_err());
+    let missing = PhoneTables {
+        ipa: String::new(),
+        ..tables
+    };
+    assert!(PronounceBuilder::from_bytes(dict, &missing).is_err());
+    // whether the text isn't csv or what's in it is wrong, it is the same error type
+    assert!(matches!(
+        parse_frameworks("React,R IY0 AE1 K T,lots"),
+        Err(LoadError::InvalidData(_))
+    ));
+    assert!(matches!(
+        parse_frameworks("React,R IY0 AE1 K T,1\nVue,V Y UW1"),
+        Err(LoadError::Csv(_))
+    ));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_matches_sequential() {
+    let text = TEXT.repeat(20);
+    let options = ReplaceOptions {
+        threshold: 1.0,
+        star_threshold: 0,
+        sampling: Some(Sampling {
+            temperature: 0.2,
+            seed: 7,
+        }),
+        variety: Some(Variety {
+            penalty: 0.05,
+            window: Some(12),
+        }),
+        stopwords: true,
+        ..ReplaceOptions::default()
+    };
+    assert_eq!(
+        replacer().par_replace_with(&text, &options),
+        replacer().replace_with(&text, &options)
+    );
+}
+
+#[test]
+fn rhymes_replace_from_the_last_stressed_vowel() {
+    let options = ReplaceOptions {
+        mode: ReplaceMode::Rhyme,
+        star_threshold: 0,
+        ..ReplaceOptions::default()
+    };
+    let rhymed = replaced(replacer().replace_with("a view with tact", &options));
+    assert_eq!(rhymed[2].as_deref(), Some("Vue"));
+    assert_eq!(rhymed[6].as_deref(), Some("React"));
+    // the whole words don't sound alike
+    let sounded = replaced(replacer().replace("tact", 0.2, 0, ReplaceMode::Sound));
+    assert_eq!(sounded, [None]);
+    let rhymed = replaced(replacer().replace("tact", 0.2, 0, ReplaceMode::Rhyme));
+    assert_eq!(rhymed[0].as_deref(), Some("React"));
+}
+
+#[test]
+fn alliteration_replaces_by_the_start_of_words() {
+    let options = ReplaceOptions {
+        mode: ReplaceMode::Alliteration,
+        star_threshold: 0,
+        ..ReplaceOptions::default()
+    };
+    let alliterated = replaced(replacer().replace_with("angry vultures never react", &options));
+    assert_eq!(alliterated[0].as_deref(), Some("Angular"));
+    assert_eq!(alliterated[2].as_deref(), Some("Vulcan Next"));
+    assert_eq!(alliterated[4].as_deref(), Some("Next"));
+}
@@ -0,0 +1,83 @@
+//! Tests for plugging pronunciation sources into `PronounceBuilder`.
+
+use std::collections::HashMap;
+use vite_wasm_functions::framework_data::read_frameworks;
+use vite_wasm_functions::pronounce::{Phone, PronounceBuilder, PronunciationSource, SpanishSource};
+use vite_wasm_functions::replacer::{ReplaceMode, Replacement, Replacer};
+
+fn phones(builder: &PronounceBuilder, word: &str) -> Vec<Phone> {
+    builder.pronounce(word).phones().collect()
+}
+fn spanish(builder: &PronounceBuilder, word: &str) -> Vec<Phone> {
+    SpanishSource.phones(word, builder).unwrap()
+}
+fn arpabet(builder: &PronounceBuilder, arpabet: &str) -> Vec<Phone> {
+    builder
+        .pronounce_from_arpabet(arpabet)
+        .unwrap()
+        .phones()
+        .collect()
+}
+
+#[test]
+fn spanish_spelling_rules() {
+    let builder = PronounceBuilder::new();
+    assert_eq!(spanish(&builder, "casa"), arpabet(&builder, "K AA S AA"));
+    assert_eq!(spanish(&builder, "Guerra"), arpabet(&builder, "G EH R AA"));
+    assert_eq!(spanish(&builder, "cielo"), arpabet(&builder, "S Y EH L OW"));
+    assert_eq!(spanish(&builder, "hoy"), arpabet(&builder, "OY"));
+    assert_eq!(spanish(&builder, "niño"), arpabet(&builder, "N IY N Y OW"));
+}
+
+#[test]
+fn spanish_only_reads_words_the_dictionary_lacks() {
+    let english = PronounceBuilder::new();
+    let mut builder = PronounceBuilder::new();
+    builder.add_fallback_source(SpanishSource);
+    for word in ["the", "react", "casa"] {
+        assert_eq!(phones(&builder, word), phones(&english, word), "{}", word);
+    }
+    assert_eq!(phones(&builder, "canción"), spanish(&builder, "canción"));
+}
+
+#[test]
+fn accented_words_are_replaced() {
+    let mut replacer = Replacer::new(read_frameworks());
+    replacer.add_fallback_source(SpanishSource);
+    // cmudict doesn't have "biú", but read as Spanish it sounds like Vue
+    let replaced = replacer.replace("Biú", 0.5, 0, ReplaceMode::Sound);
+    assert!(
+        matches!(&replaced[0], Replacement::Replace(word, _) if word == "Vue"),
+        "{:?}",
+        replaced
+    );
+}
+
+struct Lexicon(HashMap<&'static str, &'static str>);
+impl PronunciationSource for Lexicon {
+    fn phones(&self, word: &str, builder: &PronounceBuilder) -> Option<Vec<Phone>> {
+        let arpabet = self.0.get(word.to_lowercase().as_str())?;
+        Some(
+            builder
+                .pronounce_from_arpabet(arpabet)
+                .unwrap()
+                .phones()
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn sources_added_later_come_first() {
+    let mut replacer = Replacer::new(read_frameworks());
+    assert!(matches!(
+        &replacer.replace("Zug", 0.5, 0, ReplaceMode::Sound)[0],
+        Replacement::Keep(_)
+    ));
+    // a made up word said like react
+    replacer.add_source(Lexicon(HashMap::from([("zug", "R IY0 AE1 K T")])));
+    assert!(matches!(
+        &replacer.replace("Zug", 0.5, 0, ReplaceMode::Sound)[0],
+        Replacement::Replace(word, _) if word == "React"
+    ));
+}
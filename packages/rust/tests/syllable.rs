@@ -0,0 +1,74 @@
+//! Checks that `Pronounce::syllables` splits words by the maximal onset principle.
+
+use vite_wasm_functions::pronounce::{Pronounce, PronounceBuilder};
+
+// each syllable of a word in IPA
+fn syllables(builder: &PronounceBuilder, word: &str) -> Vec<String> {
+    builder
+        .pronounce(word)
+        .syllables()
+        .iter()
+        .map(|syllable| {
+            let phones = syllable
+                .onset
+                .iter()
+                .chain([&syllable.nucleus])
+                .chain(syllable.coda)
+                .copied()
+                .collect();
+            builder.to_ipa(&builder.from_phones(phones))
+        })
+        .collect()
+}
+
+#[test]
+fn consonants_start_syllables_when_english_allows() {
+    let builder = PronounceBuilder::new();
+    assert_eq!(syllables(&builder, "extra"), ["ɛk", "stɹʌ"]);
+    assert_eq!(syllables(&builder, "angular"), ["æŋ", "ɡjʌ", "lɝ"]);
+    assert_eq!(syllables(&builder, "react"), ["ɹi", "ækt"]);
+    assert_eq!(syllables(&builder, "framework"), ["fɹeɪm", "wɝk"]);
+    assert!(syllables(&builder, "notaword").is_empty());
+}
+
+#[test]
+fn only_the_builder_knows_english_onsets() {
+    let builder = PronounceBuilder::new();
+    let phones: Vec<_> = builder.pronounce("extra").phones().collect();
+    let onsets = |pronounce: &Pronounce| -> Vec<usize> {
+        let syllables = pronounce.syllables();
+        syllables
+            .iter()
+            .map(|syllable| syllable.onset.len())
+            .collect()
+    };
+    // without phone names every syllable after the first starts with one consonant
+    assert_eq!(onsets(&Pronounce::new(phones.clone())), [0, 1]);
+    assert_eq!(onsets(&builder.from_phones(phones.clone())), [0, 3]);
+    assert_eq!(
+        onsets(&builder.from_phones(phones)),
+        onsets(&builder.pronounce("extra"))
+    );
+}
+
+#[test]
+fn syllables_cover_every_phone() {
+    let builder = PronounceBuilder::new();
+    for word in ["javascript", "strength", "atmosphere", "a", "idea"] {
+        let pronounce = builder.pronounce(word);
+        let syllables = pronounce.syllables();
+        assert_eq!(syllables.len() as u32, pronounce.syllable_count());
+        let phones: Vec<_> = syllables
+            .iter()
+            .flat_map(|syllable| {
+                syllable
+                    .onset
+                    .iter()
+                    .chain([&syllable.nucleus])
+                    .chain(syllable.coda)
+                    .copied()
+            })
+            .collect();
+        assert_eq!(phones, pronounce.phones().collect::<Vec<_>>(), "{}", word);
+    }
+}